@@ -0,0 +1,65 @@
+// Highlighter timings on large notes, run with `cargo bench --bench highlight`.
+use std::time::Instant;
+
+use sunrise::{
+    style::{parse, CachedLayoutJobBuilder},
+    theme::Theme,
+};
+
+const SAMPLE: &str = "# Standup
+[ ] review the release notes @2026-10-20 #work !1
+[/] fix the flaky test, see https://example.com/issues/42
+    [ ] sub-task with `inline code`
+[x] cancelled thing
+some plain text about the day that goes on for a while #journal
+```rust
+fn main() { let x = \"string\"; // comment
+```
+## Notes
+";
+
+fn note(lines: usize) -> String {
+    SAMPLE.lines().cycle().take(lines).map(|x| format!("{}\n", x)).collect()
+}
+
+fn bench(name: &str, runs: u32, mut f: impl FnMut()) {
+    f();
+    let start = Instant::now();
+    for _ in 0..runs {
+        f();
+    }
+    let each = start.elapsed() / runs;
+    println!("{:<40} {:>12?}", name, each);
+}
+
+fn main() {
+    let theme = Theme::dark();
+    for lines in [100, 1_000, 10_000] {
+        let text = note(lines);
+        println!("{} lines ({} bytes)", lines, text.len());
+
+        bench("whole note parse", 10, || {
+            parse(&text).unwrap();
+        });
+        bench("cold cache", 10, || {
+            CachedLayoutJobBuilder::default().highlight(&text, &theme);
+        });
+
+        let mut cache = CachedLayoutJobBuilder::default();
+        cache.highlight(&text, &theme);
+        bench("unchanged note", 100, || {
+            cache.highlight(&text, &theme);
+        });
+
+        // typing at the end of the first line
+        let mut edited = text.clone();
+        let at = edited.find('\n').unwrap();
+        let mut i = 0;
+        bench("one line changed", 100, || {
+            i += 1;
+            edited.insert(at, char::from(b'a' + (i % 26) as u8));
+            cache.highlight(&edited, &theme);
+        });
+        println!();
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{
+    style::is_header,
+    tasks::{self, TaskStatus},
+};
+
+// What gets copied from the previous note when a new day is started
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CarryOver {
+    #[default]
+    Everything,
+    OpenTasks,
+    OpenTasksAndHeaders,
+    Nothing,
+}
+
+fn is_open_task(line: &str) -> bool {
+    TaskStatus::parse_line(line).is_some_and(|(status, _)| !status.is_closed())
+}
+
+impl CarryOver {
+    pub const ALL: [CarryOver; 4] = [
+        CarryOver::Everything,
+        CarryOver::OpenTasks,
+        CarryOver::OpenTasksAndHeaders,
+        CarryOver::Nothing,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CarryOver::Everything => "Everything",
+            CarryOver::OpenTasks => "Open tasks",
+            CarryOver::OpenTasksAndHeaders => "Open tasks and headers",
+            CarryOver::Nothing => "Nothing",
+        }
+    }
+
+    // stable name used in the settings file
+    pub fn key(&self) -> &'static str {
+        match self {
+            CarryOver::Everything => "everything",
+            CarryOver::OpenTasks => "open_tasks",
+            CarryOver::OpenTasksAndHeaders => "open_tasks_and_headers",
+            CarryOver::Nothing => "nothing",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        CarryOver::ALL.into_iter().find(|x| x.key() == key)
+    }
+
+    pub fn apply(&self, previous: &str) -> String {
+        let keep: fn(&str) -> bool = match self {
+            CarryOver::Everything => return previous.to_owned(),
+            CarryOver::Nothing => return String::new(),
+            CarryOver::OpenTasks => is_open_task,
+            CarryOver::OpenTasksAndHeaders => |l| is_open_task(l) || is_header(l),
+        };
+
+        // a task stays while any of its sub-tasks are still open
+        let lines: Vec<&str> = previous.lines().collect();
+        let parents = tasks::parents(&lines);
+        let mut kept: Vec<bool> = lines.iter().map(|l| keep(l)).collect();
+        for i in (0..lines.len()).rev() {
+            if let Some(parent) = parents[i].filter(|_| kept[i]) {
+                kept[parent] = true;
+            }
+        }
+
+        let mut out = String::new();
+        for (line, _) in lines.iter().zip(kept).filter(|(_, keep)| *keep) {
+            out.push_str(line);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = "# Work
+[ ] open
+[/] done
+[x] cancelled
+[~] started
+some text
+";
+
+    #[test]
+    fn everything_and_nothing() {
+        assert_eq!(CarryOver::Everything.apply(NOTE), NOTE);
+        assert_eq!(CarryOver::Nothing.apply(NOTE), "");
+    }
+
+    #[test]
+    fn open_tasks() {
+        assert_eq!(CarryOver::OpenTasks.apply(NOTE), "[ ] open\n[~] started\n");
+        assert_eq!(CarryOver::OpenTasksAndHeaders.apply(NOTE), "# Work\n[ ] open\n[~] started\n");
+        assert_eq!(CarryOver::OpenTasks.apply("- [ ] buy milk\n- [/] bought\n"), "- [ ] buy milk\n");
+    }
+
+    #[test]
+    fn parents_stay_with_open_sub_tasks() {
+        let note = "[/] parent\n    [/] done\n    [ ] open\n[/] finished\n    [/] done too\n";
+        assert_eq!(CarryOver::OpenTasks.apply(note), "[/] parent\n    [ ] open\n");
+    }
+
+    #[test]
+    fn keys_round_trip() {
+        for carry_over in CarryOver::ALL {
+            assert_eq!(CarryOver::from_key(carry_over.key()), Some(carry_over));
+        }
+        assert_eq!(CarryOver::from_key("sometimes"), None);
+    }
+}
@@ -0,0 +1,313 @@
+use std::path::PathBuf;
+
+use crate::{
+    export::to_markdown,
+    git::GitBackend,
+    html::{export_site, range_to_html},
+    import::{import_dir, ConflictPolicy},
+    tasks::TaskStatus,
+    theme::Theme,
+    BufferId, SavedFiles,
+};
+
+pub const USAGE: &str = r"usage: sunrise [--notes-dir <dir>] [--demo]
+       sunrise [--notes-dir <dir>] show [<date>]
+       sunrise [--notes-dir <dir>] append <text> [--date <date>]
+       sunrise [--notes-dir <dir>] today [--print]
+       sunrise [--notes-dir <dir>] tasks [--open] [<date>]
+       sunrise [--notes-dir <dir>] export <from> <to> [--html] [--output <file>]
+       sunrise [--notes-dir <dir>] site <dir>
+       sunrise [--notes-dir <dir>] import <dir> [--append | --overwrite]
+
+<date> is 'today', 'yesterday' or YYYY-MM-DD, YYYY-Www and YYYY-MM are weekly and monthly notes";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Options {
+    pub demo: bool,
+    pub notes_dir: Option<PathBuf>,
+    // None means the window should be opened
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Show(BufferId),
+    Append(BufferId, String),
+    Today { print: bool },
+    Tasks { id: BufferId, open: bool },
+    Export { from: BufferId, to: BufferId, html: bool, output: Option<PathBuf> },
+    Site(PathBuf),
+    Import(PathBuf, ConflictPolicy),
+    Help,
+}
+
+fn parse_date(s: &str) -> Result<BufferId, String> {
+    BufferId::parse(s).ok_or(format!("invalid date '{}'", s))
+}
+
+pub fn parse(args: &[String]) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.iter().map(|x| x.as_str()).peekable();
+    while let Some(arg) = args.peek() {
+        match *arg {
+            "--demo" => options.demo = true,
+            "--notes-dir" => {
+                args.next();
+                options.notes_dir = Some(args.peek().ok_or("--notes-dir needs a value")?.into());
+            }
+            _ => break,
+        }
+        args.next();
+    }
+
+    let command = match args.next() {
+        None => return Ok(options),
+        Some("help") | Some("--help") | Some("-h") => Command::Help,
+        Some("show") => {
+            let id = match args.next() {
+                Some(d) => parse_date(d)?,
+                None => BufferId::today(),
+            };
+            Command::Show(id)
+        }
+        Some("append") => {
+            let mut id = BufferId::today();
+            let mut text = None;
+            while let Some(arg) = args.next() {
+                match arg {
+                    "--date" => id = parse_date(args.next().ok_or("--date needs a value")?)?,
+                    _ if text.is_none() => text = Some(arg.to_owned()),
+                    _ => return Err(format!("unexpected argument '{}'", arg)),
+                }
+            }
+            Command::Append(id, text.ok_or("nothing to append")?)
+        }
+        Some("today") => {
+            let mut print = false;
+            for arg in args.by_ref() {
+                match arg {
+                    "--print" => print = true,
+                    _ => return Err(format!("unexpected argument '{}'", arg)),
+                }
+            }
+            Command::Today { print }
+        }
+        Some("tasks") => {
+            let mut id = None;
+            let mut open = false;
+            for arg in args.by_ref() {
+                match arg {
+                    "--open" => open = true,
+                    _ if id.is_none() => id = Some(parse_date(arg)?),
+                    _ => return Err(format!("unexpected argument '{}'", arg)),
+                }
+            }
+            Command::Tasks { id: id.unwrap_or_else(BufferId::today), open }
+        }
+        Some("export") => {
+            let mut dates = Vec::new();
+            let mut html = false;
+            let mut output = None;
+            while let Some(arg) = args.next() {
+                match arg {
+                    "--html" => html = true,
+                    "--output" | "-o" => output = Some(args.next().ok_or("--output needs a value")?.into()),
+                    _ => dates.push(parse_date(arg)?),
+                }
+            }
+            match dates[..] {
+                [from, to] => Command::Export { from, to, html, output },
+                _ => return Err("export needs a <from> and <to> date".to_owned()),
+            }
+        }
+        Some("site") => Command::Site(args.next().ok_or("site needs a directory")?.into()),
+        Some("import") => {
+            let mut dir = None;
+            let mut policy = ConflictPolicy::Skip;
+            for arg in args.by_ref() {
+                match arg {
+                    "--append" => policy = ConflictPolicy::Append,
+                    "--overwrite" => policy = ConflictPolicy::Overwrite,
+                    _ if dir.is_none() => dir = Some(PathBuf::from(arg)),
+                    _ => return Err(format!("unexpected argument '{}'", arg)),
+                }
+            }
+            Command::Import(dir.ok_or("import needs a directory")?, policy)
+        }
+        Some(other) => return Err(format!("unknown command '{}'", other)),
+    };
+
+    match args.next() {
+        Some(extra) => Err(format!("unexpected argument '{}'", extra)),
+        None => {
+            options.command = Some(command);
+            Ok(options)
+        }
+    }
+}
+
+// returns the process exit code, html is exported in the colours of 'theme'
+pub fn run(command: Command, mut saved_files: SavedFiles, git: Option<GitBackend>, theme: &Theme) -> i32 {
+    let mut buf = String::new();
+    let result = match command {
+        Command::Help => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Command::Show(id) => saved_files.load(&id, &mut buf).map(|_| print!("{}", buf)),
+        Command::Append(id, text) => {
+            let loaded = if id == BufferId::today() {
+                saved_files.load_today(&mut buf)
+            } else if saved_files.has(&id) {
+                saved_files.load(&id, &mut buf)
+            } else {
+                Ok(())
+            };
+            loaded.and_then(|_| {
+                if !buf.is_empty() && !buf.ends_with('\n') {
+                    buf.push('\n');
+                }
+                buf.push_str(&text);
+                buf.push('\n');
+                saved_files.save(&id, &buf)
+            })
+            .and_then(|_| match &git {
+                Some(git) => match git.commit_all(&format!("Append to {}", id.date.format("%Y-%m-%d")))? {
+                    true => git.sync(),
+                    false => Ok(()),
+                },
+                None => Ok(()),
+            })
+        }
+        Command::Today { print } => {
+            let today = BufferId::today();
+            saved_files
+                .load_today(&mut buf)
+                // nothing was carried over or due, the note is still created so the path can be opened
+                .and_then(|_| if saved_files.has(&today) { Ok(()) } else { saved_files.save(&today, &buf) })
+                .map(|_| {
+                    if print {
+                        print!("{}", buf);
+                    } else {
+                        let mut path = saved_files.root_dir();
+                        path.push(today.filepath());
+                        println!("{}", path.display());
+                    }
+                })
+        }
+        Command::Tasks { id, open } => saved_files.load(&id, &mut buf).map(|_| {
+            for line in buf.lines() {
+                let status = TaskStatus::parse_line(line).map(|x| x.0);
+                if (open && status.is_some_and(|x| !x.is_closed())) || (!open && status.is_some()) {
+                    println!("{}", line);
+                }
+            }
+        }),
+        Command::Export { from, to, html, output } => {
+            let exported = if html {
+                range_to_html(&mut saved_files, &from, &to, theme)
+            } else {
+                to_markdown(&mut saved_files, &from, &to)
+            };
+            match output {
+                Some(path) => std::fs::write(path, exported),
+                None => {
+                    print!("{}", exported);
+                    Ok(())
+                }
+            }
+        }
+        Command::Site(dir) => export_site(&mut saved_files, &dir, theme),
+        Command::Import(dir, policy) => import_dir(&mut saved_files, &dir, policy).map(|report| {
+            println!("{}", report.summary());
+        }),
+    };
+
+    match result {
+        Ok(_) => 0,
+        Err(e) => {
+            eprintln!("sunrise: {}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(|x| x.to_owned()).collect()
+    }
+
+    fn command(s: &str) -> Result<Command, String> {
+        parse(&args(s)).map(|x| x.command.unwrap())
+    }
+
+    #[test]
+    fn no_command_opens_the_window() {
+        let options = parse(&args("--demo --notes-dir /tmp/notes")).unwrap();
+        assert!(options.demo);
+        assert_eq!(options.notes_dir, Some(PathBuf::from("/tmp/notes")));
+        assert_eq!(options.command, None);
+    }
+
+    #[test]
+    fn notes_dir_needs_a_value() {
+        assert!(parse(&args("--notes-dir")).is_err());
+    }
+
+    #[test]
+    fn dates() {
+        let day = BufferId::parse("2026-10-17").unwrap();
+        assert_eq!(command("show 2026-10-17"), Ok(Command::Show(day)));
+        assert_eq!(command("show"), Ok(Command::Show(BufferId::today())));
+        assert_eq!(command("show today"), Ok(Command::Show(BufferId::today())));
+        assert_eq!(command("show yesterday"), Ok(Command::Show(BufferId::yesterday())));
+        assert!(command("show 2026-13-01").is_err());
+    }
+
+    #[test]
+    fn append() {
+        let day = BufferId::parse("2026-10-17").unwrap();
+        assert_eq!(command("append hello --date 2026-10-17"), Ok(Command::Append(day, "hello".to_owned())));
+        assert_eq!(command("append hello"), Ok(Command::Append(BufferId::today(), "hello".to_owned())));
+        assert!(command("append").is_err());
+        assert!(command("append hello there").is_err());
+        assert!(command("append hello --date").is_err());
+    }
+
+    #[test]
+    fn tasks() {
+        let day = BufferId::parse("2026-10-17").unwrap();
+        assert_eq!(command("tasks --open 2026-10-17"), Ok(Command::Tasks { id: day, open: true }));
+        assert_eq!(command("tasks"), Ok(Command::Tasks { id: BufferId::today(), open: false }));
+        assert!(command("tasks 2026-10-17 2026-10-18").is_err());
+    }
+
+    #[test]
+    fn export() {
+        let from = BufferId::parse("2026-10-01").unwrap();
+        let to = BufferId::parse("2026-10-17").unwrap();
+        assert_eq!(
+            command("export 2026-10-01 2026-10-17 --html -o out.html"),
+            Ok(Command::Export { from, to, html: true, output: Some("out.html".into()) })
+        );
+        assert!(command("export 2026-10-01").is_err());
+        assert!(command("export 2026-10-01 2026-10-17 2026-10-18").is_err());
+    }
+
+    #[test]
+    fn import() {
+        assert_eq!(command("import notes --append"), Ok(Command::Import("notes".into(), ConflictPolicy::Append)));
+        assert_eq!(command("import notes"), Ok(Command::Import("notes".into(), ConflictPolicy::Skip)));
+        assert!(command("import").is_err());
+    }
+
+    #[test]
+    fn unknown() {
+        assert!(command("frobnicate").is_err());
+        assert!(command("today --print extra").is_err());
+        assert!(command("site out extra").is_err());
+    }
+}
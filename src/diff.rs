@@ -0,0 +1,79 @@
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Same(String),
+    Added(String),
+    Removed(String),
+}
+
+// line based diff from the longest common subsequence, notes are small enough for the full table
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<_> = old.lines().collect();
+    let new: Vec<_> = new.lines().collect();
+
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            out.push(DiffLine::Same(old[i].to_owned()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            out.push(DiffLine::Removed(old[i].to_owned()));
+            i += 1;
+        } else {
+            out.push(DiffLine::Added(new[j].to_owned()));
+            j += 1;
+        }
+    }
+    out.extend(old[i..].iter().map(|x| DiffLine::Removed(x.to_string())));
+    out.extend(new[j..].iter().map(|x| DiffLine::Added(x.to_string())));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn same(s: &str) -> DiffLine {
+        DiffLine::Same(s.to_owned())
+    }
+    fn added(s: &str) -> DiffLine {
+        DiffLine::Added(s.to_owned())
+    }
+    fn removed(s: &str) -> DiffLine {
+        DiffLine::Removed(s.to_owned())
+    }
+
+    #[test]
+    fn identical() {
+        assert_eq!(diff_lines("a\nb\n", "a\nb\n"), [same("a"), same("b")]);
+        assert_eq!(diff_lines("", ""), []);
+    }
+
+    #[test]
+    fn added_and_removed() {
+        assert_eq!(diff_lines("a\nc\n", "a\nb\nc\n"), [same("a"), added("b"), same("c")]);
+        assert_eq!(diff_lines("a\nb\nc\n", "a\nc\n"), [same("a"), removed("b"), same("c")]);
+        assert_eq!(diff_lines("", "a\n"), [added("a")]);
+        assert_eq!(diff_lines("a\n", ""), [removed("a")]);
+    }
+
+    #[test]
+    fn changed_line() {
+        assert_eq!(
+            diff_lines("[ ] task\nnotes\n", "[/] task\nnotes\nmore\n"),
+            [removed("[ ] task"), added("[/] task"), same("notes"), added("more")]
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use directories::UserDirs;
+use eframe::egui;
+
+use crate::{
+    html::{export_site, range_to_html},
+    style::{is_header, next_fence},
+    tasks::{self, TaskStatus},
+    theme::Theme,
+    BufferId, SavedFiles,
+};
+
+// days with a note between 'from' and 'to' inclusive, oldest first
+pub fn days_in_range(saved_files: &SavedFiles, from: &BufferId, to: &BufferId) -> Vec<BufferId> {
+    let mut ids: Vec<_> = saved_files
+        .list()
+        .into_iter()
+        .filter(|x| x.is_day() && x.date >= from.date && x.date <= to.date)
+        .collect();
+    ids.sort_by_key(|x| x.date);
+    ids
+}
+
+// headers are demoted a level to sit under the day's header and tasks become GitHub checkboxes
+fn markdown_line(line: &str) -> String {
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let rest = line.trim_start();
+    if is_header(line) {
+        return format!("{}#{}", indent, rest);
+    }
+    // tasks already written as list items keep their marker, e.g. "1. "
+    let item = match tasks::list_prefix(line) {
+        n if n > indent.len() => line[..n].to_owned(),
+        _ => format!("{}- ", indent),
+    };
+    match TaskStatus::parse_line(line) {
+        Some((TaskStatus::Open, text)) => format!("{}[ ] {}", item, text),
+        Some((status @ (TaskStatus::InProgress | TaskStatus::Blocked | TaskStatus::Deferred), text)) => {
+            format!("{}[ ] {} ({})", item, text, status.name().to_lowercase())
+        }
+        Some((TaskStatus::Completed, text)) => format!("{}[x] {}", item, text),
+        Some((TaskStatus::Cancelled, text)) => format!("{}[x] ~~{}~~", item, text),
+        None => line.to_owned(),
+    }
+}
+
+pub fn to_markdown(saved_files: &mut SavedFiles, from: &BufferId, to: &BufferId) -> String {
+    let mut out = String::new();
+    let mut buf = String::new();
+    for id in days_in_range(saved_files, from, to) {
+        if saved_files.load(&id, &mut buf).is_err() {
+            continue;
+        }
+        out.push_str(&format!("# {}\n\n", id.date.format("%Y-%m-%d (%A)")));
+        out.push_str(&note_to_markdown(&buf));
+        out.push('\n');
+    }
+    out
+}
+
+// every line of a note converted, apart from code in ``` fences which is copied as it is
+fn note_to_markdown(text: &str) -> String {
+    let mut out = String::new();
+    let mut fence = None;
+    for line in text.lines() {
+        if fence.is_some() {
+            out.push_str(line);
+        } else {
+            out.push_str(&markdown_line(line));
+        }
+        out.push('\n');
+        next_fence(line, &mut fence);
+    }
+    out
+}
+
+fn default_export_dir() -> PathBuf {
+    UserDirs::new()
+        .and_then(|x| x.document_dir().map(|x| x.to_path_buf()))
+        .unwrap_or_else(|| ".".into())
+}
+
+// state of the Export menu
+#[derive(Debug)]
+pub struct ExportDialog {
+    from: String,
+    to: String,
+    path: String,
+    status: Option<String>,
+}
+
+impl Default for ExportDialog {
+    fn default() -> Self {
+        let today = Local::today();
+        let mut path = default_export_dir();
+        path.push("sunrise");
+        Self {
+            from: (today - chrono::Duration::days(6)).format("%Y-%m-%d").to_string(),
+            to: today.format("%Y-%m-%d").to_string(),
+            path: path.to_string_lossy().into_owned(),
+            status: None,
+        }
+    }
+}
+
+impl ExportDialog {
+    // the range is written to the export path with 'extension' added
+    fn export_range(&mut self, saved_files: &mut SavedFiles, extension: &str, render: impl FnOnce(&mut SavedFiles, &BufferId, &BufferId) -> String) {
+        self.status = Some(match (BufferId::parse(self.from.trim()), BufferId::parse(self.to.trim())) {
+            (Some(from), Some(to)) => {
+                let path = Path::new(&self.path).with_extension(extension);
+                match std::fs::write(&path, render(saved_files, &from, &to)) {
+                    Ok(_) => format!("Exported to {}", path.display()),
+                    Err(e) => e.to_string(),
+                }
+            }
+            _ => "Dates should be YYYY-MM-DD".to_owned(),
+        });
+    }
+
+    // html is written in the colours of 'theme'
+    pub fn show(&mut self, ui: &mut egui::Ui, saved_files: &mut SavedFiles, theme: &Theme) {
+        egui::Grid::new("export").num_columns(2).show(ui, |ui| {
+            ui.label("From");
+            ui.text_edit_singleline(&mut self.from);
+            ui.end_row();
+            ui.label("To");
+            ui.text_edit_singleline(&mut self.to);
+            ui.end_row();
+            ui.label("Export to");
+            ui.text_edit_singleline(&mut self.path);
+            ui.end_row();
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button("Markdown").clicked() {
+                self.export_range(saved_files, "md", to_markdown);
+            }
+            if ui.button("HTML").clicked() {
+                self.export_range(saved_files, "html", |saved_files, from, to| range_to_html(saved_files, from, to, theme));
+            }
+            if ui.button("Site").on_hover_text("Every note as a browsable folder of HTML pages").clicked() {
+                self.status = Some(match export_site(saved_files, Path::new(&self.path), theme) {
+                    Ok(_) => format!("Exported to {}", self.path),
+                    Err(e) => e.to_string(),
+                });
+            }
+        });
+
+        if let Some(status) = &self.status {
+            ui.small(status);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headers_are_demoted() {
+        assert_eq!(markdown_line("# Work"), "## Work");
+        assert_eq!(markdown_line("  ## Sub"), "  ### Sub");
+        assert_eq!(markdown_line("#tag on its own"), "#tag on its own");
+        assert_eq!(markdown_line("##Plans"), "###Plans");
+    }
+
+    #[test]
+    fn tasks_become_checkboxes() {
+        assert_eq!(markdown_line("[ ] open"), "- [ ] open");
+        assert_eq!(markdown_line("    [/] done"), "    - [x] done");
+        assert_eq!(markdown_line("[x] dropped"), "- [x] ~~dropped~~");
+        assert_eq!(markdown_line("[~] started"), "- [ ] started (in progress)");
+        assert_eq!(markdown_line("[!] stuck"), "- [ ] stuck (blocked)");
+        assert_eq!(markdown_line("[>] later"), "- [ ] later (deferred)");
+        assert_eq!(markdown_line("  - [/] done"), "  - [x] done");
+        assert_eq!(markdown_line("1. [ ] first"), "1. [ ] first");
+    }
+
+    #[test]
+    fn fenced_code_is_copied() {
+        let note = "# Setup\n```sh\n#!/bin/bash\n# comment\n[ ] x\n```\n[ ] after";
+        assert_eq!(note_to_markdown(note), "## Setup\n```sh\n#!/bin/bash\n# comment\n[ ] x\n```\n- [ ] after\n");
+    }
+
+    #[test]
+    fn text_is_unchanged() {
+        assert_eq!(markdown_line("just some text"), "just some text");
+        assert_eq!(markdown_line(""), "");
+    }
+}
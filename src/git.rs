@@ -0,0 +1,258 @@
+use std::{
+    io::Error,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::settings::Settings;
+
+// written next to a note while it's saved
+const IGNORED: [&str; 2] = ["*.tmp", "*.bak"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revision {
+    pub hash: String,
+    pub date: String,
+    pub summary: String,
+}
+
+// keeps the notes directory in a git repository using the local git binary
+#[derive(Debug, Clone)]
+pub struct GitBackend {
+    dir: PathBuf,
+    remote: Option<String>,
+}
+
+// git wants forward slashes in 'rev:path' even on windows
+fn git_path(path: &Path) -> String {
+    path.iter()
+        .map(|x| x.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+impl GitBackend {
+    pub fn new(dir: PathBuf, remote: Option<String>) -> Self {
+        Self { dir, remote }
+    }
+
+    // None unless git history is turned on in the settings
+    pub fn from_settings(settings: &Settings, dir: PathBuf) -> Option<Self> {
+        if !settings.git_enabled {
+            return None;
+        }
+        let remote = Some(settings.git_remote.trim().to_owned()).filter(|x| !x.is_empty());
+        Some(Self::new(dir, remote))
+    }
+
+    fn git(&self, args: &[&str]) -> Result<String, Error> {
+        let output = Command::new("git").arg("-C").arg(&self.dir).args(args).output()?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(Error::other(format!("git {}: {}", args[0], stderr.trim())))
+        }
+    }
+
+    // creates the repository if needed and points 'origin' at the configured remote
+    pub fn init(&self) -> Result<(), Error> {
+        std::fs::create_dir_all(&self.dir)?;
+        if !self.dir.join(".git").exists() {
+            self.git(&["init"])?;
+        }
+        self.ignore_saving_files()?;
+        if let Some(remote) = &self.remote {
+            if self.git(&["remote", "set-url", "origin", remote]).is_err() {
+                self.git(&["remote", "add", "origin", remote])?;
+            }
+        }
+        Ok(())
+    }
+
+    // the temporary and backup files a save leaves are kept out of history, in the repository's own
+    // exclude file so it works the same for a notes folder that was already a repository
+    fn ignore_saving_files(&self) -> Result<(), Error> {
+        let path = self.dir.join(self.git(&["rev-parse", "--git-path", "info/exclude"])?.trim());
+        let mut exclude = std::fs::read_to_string(&path).unwrap_or_default();
+        let missing: Vec<_> = IGNORED.iter().filter(|x| !exclude.lines().any(|line| line.trim() == **x)).collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        if !exclude.is_empty() && !exclude.ends_with('\n') {
+            exclude.push('\n');
+        }
+        for pattern in missing {
+            exclude.push_str(pattern);
+            exclude.push('\n');
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, exclude)
+    }
+
+    // returns false if there was nothing to commit
+    pub fn commit_all(&self, message: &str) -> Result<bool, Error> {
+        self.init()?;
+        self.git(&["add", "-A"])?;
+        if self.git(&["diff", "--cached", "--quiet"]).is_ok() {
+            return Ok(false);
+        }
+        self.git(&[
+            "-c",
+            "user.name=sunrise",
+            "-c",
+            "user.email=sunrise@localhost",
+            "commit",
+            "--quiet",
+            "-m",
+            message,
+        ])?;
+        Ok(true)
+    }
+
+    // rebase onto the remote (if it has anything yet) then push
+    pub fn sync(&self) -> Result<(), Error> {
+        if self.remote.is_none() {
+            return Ok(());
+        }
+        let branch = self.git(&["rev-parse", "--abbrev-ref", "HEAD"])?;
+        let branch = branch.trim();
+        self.git(&["fetch", "--quiet", "origin"])?;
+        let remote_branch = format!("origin/{}", branch);
+        if self.git(&["rev-parse", "--verify", "--quiet", &remote_branch]).is_ok() {
+            let rebased = self.git(&[
+                "-c",
+                "user.name=sunrise",
+                "-c",
+                "user.email=sunrise@localhost",
+                "rebase",
+                "--quiet",
+                &remote_branch,
+            ]);
+            if let Err(e) = rebased {
+                // leave the local history as it was, the conflict has to be fixed by hand
+                let _ = self.git(&["rebase", "--abort"]);
+                return Err(e);
+            }
+        }
+        self.git(&["push", "--quiet", "origin", branch])?;
+        Ok(())
+    }
+
+    // newest first, 'path' is relative to the notes directory
+    pub fn history(&self, path: &Path) -> Result<Vec<Revision>, Error> {
+        let log = self.git(&[
+            "log",
+            "--format=%h%x09%ad%x09%s",
+            "--date=format:%Y-%m-%d %H:%M",
+            "--",
+            &git_path(path),
+        ])?;
+        Ok(log
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, '\t');
+                Some(Revision {
+                    hash: parts.next()?.to_owned(),
+                    date: parts.next()?.to_owned(),
+                    summary: parts.next().unwrap_or("").to_owned(),
+                })
+            })
+            .collect())
+    }
+
+    pub fn show(&self, revision: &Revision, path: &Path) -> Result<String, Error> {
+        self.git(&["show", &format!("{}:{}", revision.hash, git_path(path))])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    // a notes directory with a bare repository next to it as the remote
+    fn setup(name: &str) -> (TempDir, GitBackend) {
+        let tmp = TempDir::new(&format!("git-{}", name));
+        let remote = tmp.0.join("remote.git");
+        let output = Command::new("git").args(["init", "--quiet", "--bare"]).arg(&remote).output().unwrap();
+        assert!(output.status.success());
+        let git = GitBackend::new(tmp.0.join("notes"), Some(remote.to_string_lossy().into_owned()));
+        (tmp, git)
+    }
+
+    fn write(git: &GitBackend, path: &str, text: &str) {
+        let path = git.dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, text).unwrap();
+    }
+
+    #[test]
+    fn commit_history_and_show() {
+        let (_tmp, git) = setup("history");
+        let path = Path::new("2026").join("10").join("17");
+
+        write(&git, "2026/10/17", "first\n");
+        assert!(git.commit_all("one").unwrap());
+        assert!(!git.commit_all("nothing changed").unwrap());
+        write(&git, "2026/10/17", "second\n");
+        write(&git, "2026/10/17.bak", "first\n");
+        assert!(git.commit_all("two").unwrap());
+
+        let history = git.history(&path).unwrap();
+        assert_eq!(history.iter().map(|x| x.summary.as_str()).collect::<Vec<_>>(), ["two", "one"]);
+        assert_eq!(git.show(&history[0], &path).unwrap(), "second\n");
+        assert_eq!(git.show(&history[1], &path).unwrap(), "first\n");
+        // backups are ignored
+        assert!(git.history(Path::new("2026/10/17.bak")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn sync_through_a_bare_remote() {
+        let (tmp, git) = setup("sync");
+        write(&git, "2026/10/16", "from the first machine\n");
+        assert!(git.commit_all("first").unwrap());
+        git.sync().unwrap();
+
+        // a second machine with notes of its own syncing to the same remote
+        let other = GitBackend::new(tmp.0.join("other"), git.remote.clone());
+        write(&other, "2026/10/17", "from the second machine\n");
+        assert!(other.commit_all("second").unwrap());
+        other.sync().unwrap();
+
+        // the first copy picks up the other note and rebases its own change on top
+        write(&git, "2026/10/18", "first again\n");
+        assert!(git.commit_all("third").unwrap());
+        git.sync().unwrap();
+        assert_eq!(std::fs::read_to_string(git.dir.join("2026/10/17")).unwrap(), "from the second machine\n");
+        let log = git.git(&["log", "--format=%s"]).unwrap();
+        assert_eq!(log.lines().collect::<Vec<_>>(), ["third", "second", "first"]);
+    }
+
+    #[test]
+    fn backups_are_ignored_in_an_existing_repository() {
+        let tmp = TempDir::new("git-existing");
+        let git = GitBackend::new(tmp.0.join("notes"), None);
+        std::fs::create_dir_all(&git.dir).unwrap();
+        git.git(&["init", "--quiet"]).unwrap();
+        write(&git, "2026/10/17", "text\n");
+        write(&git, "2026/10/17.bak", "old\n");
+        write(&git, "2026/10/18.tmp", "half\n");
+        assert!(git.commit_all("one").unwrap());
+        assert!(git.commit_all("again").is_ok());
+        assert_eq!(git.git(&["ls-files"]).unwrap(), "2026/10/17\n");
+        let exclude = std::fs::read_to_string(git.dir.join(".git/info/exclude")).unwrap();
+        assert_eq!(exclude.matches("*.bak").count(), 1);
+    }
+
+    #[test]
+    fn no_remote_is_a_no_op() {
+        let tmp = TempDir::new("git-local");
+        let git = GitBackend::new(tmp.0.join("notes"), None);
+        write(&git, "2026/10/17", "text\n");
+        assert!(git.commit_all("one").unwrap());
+        git.sync().unwrap();
+    }
+}
@@ -0,0 +1,243 @@
+use std::path::Path;
+
+use eframe::{egui::TextFormat, epaint::{Color32, FontFamily}};
+use itertools::Itertools;
+
+use crate::{
+    export::days_in_range,
+    style::{link_target, styles, StyleKind},
+    theme::Theme,
+    BufferId, SavedFiles,
+};
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn color(c: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", c.r(), c.g(), c.b())
+}
+
+// only links that can't run script in the page get an href, relative ones have no scheme
+fn is_safe_href(url: &str) -> bool {
+    match url.find([':', '/', '?', '#']) {
+        Some(i) if url[i..].starts_with(':') => {
+            matches!(url[..i].to_ascii_lowercase().as_str(), "http" | "https" | "mailto" | "file")
+        }
+        _ => true,
+    }
+}
+
+// the page around the notes in the theme's colours
+fn page_css(theme: &Theme) -> String {
+    format!(
+        r"body {{ background: {}; color: {}; font-family: sans-serif; font-size: {}px; margin: 2em auto; max-width: 50em; }}
+.note {{ white-space: pre-wrap; }}
+.note h1, .note h2, .note h3, .note h4, .note h5, .note h6 {{ margin: 0; font-weight: normal; }}
+.cancelled {{ text-decoration: line-through; }}
+.quote {{ border-left: 3px solid {}; padding-left: 0.5em; }}
+hr {{ border: none; border-top: 1px solid {}; }}
+nav {{ margin-bottom: 1em; }}
+nav a, a.day {{ color: {}; margin-right: 1em; }}",
+        color(theme.background),
+        color(theme.text),
+        theme.font_size,
+        color(theme.quote),
+        color(theme.rule),
+        color(theme.link)
+    )
+}
+
+// the same look the editor gives the text
+fn css(look: &TextFormat) -> String {
+    let mut css = format!("color: {}; font-size: {}px;", color(look.color), look.font_id.size);
+    if look.font_id.family == FontFamily::Monospace {
+        css.push_str(" font-family: monospace;");
+    }
+    if look.background != Color32::TRANSPARENT {
+        css.push_str(&format!(" background: {};", color(look.background)));
+    }
+    css
+}
+
+// renders a note with the highlighter's parser in the colours of 'theme'
+pub fn note_to_html(text: &str, theme: &Theme) -> String {
+    let mut text = text.to_owned();
+    if !text.ends_with('\n') {
+        // line based styles expect a newline
+        text.push('\n');
+    }
+
+    let styles = styles(&text);
+    let mut out = String::from("<div class=\"note\">");
+    let mut offset = 0;
+    for style in styles {
+        let span = &text[offset..offset + style.len];
+        offset += style.len;
+        let css = css(&theme.format(style.kind));
+        match style.kind {
+            StyleKind::Header(level) => {
+                let level = level.min(6);
+                let title = span.trim().trim_start_matches('#').trim();
+                out.push_str(&format!("<h{0} style=\"{1}\">{2}</h{0}>", level, css, escape(title)));
+            }
+            StyleKind::CompletedTask => {
+                out.push_str(&format!("<span class=\"completed\" style=\"{}\">{}</span>", css, escape(span)));
+            }
+            StyleKind::CancelledTask => {
+                out.push_str(&format!("<span class=\"cancelled\" style=\"{}\">{}</span>", css, escape(span)));
+            }
+            StyleKind::Code => {
+                let code = &span[1..span.len() - 1];
+                out.push_str(&format!("<code style=\"{}\">{}</code>", css, escape(code)));
+            }
+            StyleKind::Link if is_safe_href(span) => {
+                out.push_str(&format!("<a href=\"{0}\" style=\"{1}\">{0}</a>", escape(span), css));
+            }
+            StyleKind::Link => out.push_str(&escape(span)),
+            StyleKind::Due(_)
+            | StyleKind::Tag
+            | StyleKind::Priority(_)
+            | StyleKind::InProgressTask
+            | StyleKind::BlockedTask
+            | StyleKind::DeferredTask => {
+                out.push_str(&format!("<span style=\"{}\">{}</span>", css, escape(span)));
+            }
+            StyleKind::Bold => {
+                out.push_str(&format!("<strong style=\"{}\">{}</strong>", css, escape(&span[2..span.len() - 2])));
+            }
+            StyleKind::Italic => {
+                out.push_str(&format!("<em style=\"{}\">{}</em>", css, escape(&span[1..span.len() - 1])));
+            }
+            StyleKind::Strikethrough => {
+                out.push_str(&format!("<del style=\"{}\">{}</del>", css, escape(&span[2..span.len() - 2])));
+            }
+            StyleKind::Quote => {
+                out.push_str(&format!("<span class=\"quote\" style=\"{}\">{}</span>", css, escape(span)));
+            }
+            StyleKind::ListMarker => {
+                out.push_str(&format!("<span style=\"{}\">{}</span>", css, escape(span)));
+            }
+            StyleKind::Rule => out.push_str("<hr>"),
+            StyleKind::CodeBlock(_) => {
+                out.push_str(&format!("<span class=\"block\" style=\"{}\">{}</span>", css, escape(span)));
+            }
+            StyleKind::NamedLink => {
+                let text = span[1..].split_once("](").map_or(span, |(text, _)| text);
+                match link_target(style.kind, span).filter(|x| is_safe_href(x)) {
+                    Some(url) => out.push_str(&format!("<a href=\"{}\" style=\"{}\">{}</a>", escape(url), css, escape(text))),
+                    None => out.push_str(&escape(span)),
+                }
+            }
+            StyleKind::Text => out.push_str(&escape(span)),
+        }
+    }
+    out.push_str("</div>");
+    out
+}
+
+fn page(title: &str, body: &str, theme: &Theme) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n{}\n</body>\n</html>\n",
+        escape(title),
+        page_css(theme),
+        body
+    )
+}
+
+fn day_title(id: &BufferId) -> String {
+    id.date.format("%Y-%m-%d (%A)").to_string()
+}
+
+fn day_file(id: &BufferId) -> String {
+    id.date.format("%Y-%m-%d.html").to_string()
+}
+
+// one self-contained page with a section per day
+pub fn range_to_html(saved_files: &mut SavedFiles, from: &BufferId, to: &BufferId, theme: &Theme) -> String {
+    let mut body = String::new();
+    let mut buf = String::new();
+    for id in days_in_range(saved_files, from, to) {
+        if saved_files.load(&id, &mut buf).is_ok() {
+            body.push_str(&format!("<h1>{}</h1>\n{}\n", day_title(&id), note_to_html(&buf, theme)));
+        }
+    }
+    let title = format!("{} to {}", from.date.format("%Y-%m-%d"), to.date.format("%Y-%m-%d"));
+    page(&title, &body, theme)
+}
+
+// a page per day plus an index.html linking them all
+pub fn export_site(saved_files: &mut SavedFiles, dir: &Path, theme: &Theme) -> Result<(), std::io::Error> {
+    std::fs::create_dir_all(dir)?;
+    let mut ids: Vec<_> = saved_files.list().into_iter().filter(|x| x.is_day()).collect();
+    ids.sort_by_key(|x| x.date);
+
+    let mut buf = String::new();
+    for (i, id) in ids.iter().enumerate() {
+        saved_files.load(id, &mut buf)?;
+        let mut nav = String::from("<nav><a href=\"index.html\">Index</a>");
+        if let Some(prev) = i.checked_sub(1).and_then(|i| ids.get(i)) {
+            nav.push_str(&format!("<a href=\"{}\">Previous</a>", day_file(prev)));
+        }
+        if let Some(next) = ids.get(i + 1) {
+            nav.push_str(&format!("<a href=\"{}\">Next</a>", day_file(next)));
+        }
+        nav.push_str("</nav>");
+        let body = format!("{}\n<h1>{}</h1>\n{}", nav, day_title(id), note_to_html(&buf, theme));
+        std::fs::write(dir.join(day_file(id)), page(&day_title(id), &body, theme))?;
+    }
+
+    let mut index = String::from("<h1>sunrise</h1>\n");
+    for (month, days) in &ids.iter().rev().group_by(|x| x.date.format("%B %Y").to_string()) {
+        index.push_str(&format!("<h2>{}</h2>\n<p>", month));
+        for id in days {
+            index.push_str(&format!("<a class=\"day\" href=\"{}\">{}</a>", day_file(id), id.date.format("%d %a")));
+        }
+        index.push_str("</p>\n");
+    }
+    std::fs::write(dir.join("index.html"), page("sunrise", &index, theme))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_hrefs() {
+        for url in ["https://example.com", "HTTP://x", "mailto:me@example.com", "file:///tmp/x", "notes/2026-10-17.html", "#top"] {
+            assert!(is_safe_href(url), "{}", url);
+        }
+        for url in ["javascript:alert(1)", "JavaScript://%0aalert(1)", "data:text/html,x", " javascript:x", "java\tscript:x"] {
+            assert!(!is_safe_href(url), "{}", url);
+        }
+    }
+
+    #[test]
+    fn unsafe_links_are_text() {
+        let html = note_to_html("see javascript://%0aalert(1) and https://example.com", &Theme::dark());
+        assert!(!html.contains("href=\"javascript"));
+        assert!(html.contains("href=\"https://example.com\""));
+        let html = note_to_html("[click](javascript:alert(1)) and [notes](notes/today.html)", &Theme::dark());
+        assert!(html.contains("[click](javascript:alert(1))"));
+        assert!(!html.contains("href=\"javascript"));
+        assert!(html.contains("href=\"notes/today.html\""));
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        let html = note_to_html("<script>alert(\"x\")</script> & more", &Theme::dark());
+        assert!(html.contains("&lt;script&gt;alert(&quot;x&quot;)&lt;/script&gt; &amp; more"));
+    }
+
+    #[test]
+    fn pages_use_the_theme() {
+        let light = Theme::light();
+        let html = page("day", &note_to_html("# Plans", &light), &light);
+        assert!(html.contains("body { background: #ffffff; color: #1e1e1e;"));
+        assert!(html.contains(&format!("color: {};", color(light.header))));
+        assert!(!page("day", "", &Theme::dark()).contains("#ffffff"));
+    }
+}
@@ -0,0 +1,218 @@
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use eframe::egui;
+use walkdir::WalkDir;
+
+use crate::{local_date, BufferId, SavedFiles};
+
+// what to do when a day being imported already has a note
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    #[default]
+    Skip,
+    Append,
+    Overwrite,
+}
+
+impl ConflictPolicy {
+    pub const ALL: [ConflictPolicy; 3] = [ConflictPolicy::Skip, ConflictPolicy::Append, ConflictPolicy::Overwrite];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ConflictPolicy::Skip => "Skip existing days",
+            ConflictPolicy::Append => "Append to existing days",
+            ConflictPolicy::Overwrite => "Overwrite existing days",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub imported: Vec<BufferId>,
+    // days that already had a note
+    pub conflicts: Vec<BufferId>,
+    // markdown files without a date for a name
+    pub skipped: Vec<PathBuf>,
+}
+
+impl ImportReport {
+    pub fn summary(&self) -> String {
+        let mut s = format!("Imported {} days", self.imported.len());
+        if !self.conflicts.is_empty() {
+            let days: Vec<_> = self.conflicts.iter().map(|x| x.date.format("%Y-%m-%d").to_string()).collect();
+            s.push_str(&format!("\n{} already existed: {}", days.len(), days.join(", ")));
+        }
+        if !self.skipped.is_empty() {
+            s.push_str(&format!("\n{} files skipped, their names aren't dates", self.skipped.len()));
+        }
+        s
+    }
+}
+
+// Obsidian names daily notes YYYY-MM-DD.md, Logseq uses YYYY_MM_DD.md
+fn date_from_filename(path: &Path) -> Option<BufferId> {
+    if path.extension()?.to_str()? != "md" {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?.replace(['_', '.'], "-");
+    let date = NaiveDate::parse_from_str(&stem, "%Y-%m-%d").ok()?;
+    Some(BufferId::new(local_date(date)?))
+}
+
+// markdown checkboxes and Logseq task markers become sunrise tasks
+fn convert_line(line: &str) -> String {
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let rest = line.trim_start();
+    let item = match rest.strip_prefix("- ").or_else(|| rest.strip_prefix("* ")) {
+        Some(item) => item,
+        None => return line.to_owned(),
+    };
+
+    let markers = [
+        ("[ ] ", "[ ]"),
+        ("[x] ", "[/]"),
+        ("[X] ", "[/]"),
+        ("[-] ", "[x]"),
+        ("[>] ", "[>]"),
+        ("TODO ", "[ ]"),
+        ("LATER ", "[ ]"),
+        ("NOW ", "[~]"),
+        ("DOING ", "[~]"),
+        ("WAITING ", "[!]"),
+        ("DONE ", "[/]"),
+        ("CANCELED ", "[x]"),
+        ("CANCELLED ", "[x]"),
+    ];
+    for (from, to) in markers {
+        if let Some(text) = item.strip_prefix(from) {
+            return format!("{}{} {}", indent, to, text);
+        }
+    }
+    line.to_owned()
+}
+
+fn convert(markdown: &str) -> String {
+    let mut out = String::new();
+    for line in markdown.lines() {
+        out.push_str(&convert_line(line));
+        out.push('\n');
+    }
+    out
+}
+
+pub fn import_dir(saved_files: &mut SavedFiles, dir: &Path, policy: ConflictPolicy) -> Result<ImportReport, std::io::Error> {
+    let mut report = ImportReport::default();
+    let mut existing = String::new();
+    for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !path.is_file() || path.extension().and_then(|x| x.to_str()) != Some("md") {
+            continue;
+        }
+        let id = match date_from_filename(path) {
+            Some(id) => id,
+            None => {
+                report.skipped.push(path.to_path_buf());
+                continue;
+            }
+        };
+
+        let mut text = convert(&std::fs::read_to_string(path)?);
+        if saved_files.has(&id) {
+            report.conflicts.push(id);
+            match policy {
+                ConflictPolicy::Skip => continue,
+                ConflictPolicy::Append => {
+                    saved_files.load(&id, &mut existing)?;
+                    if !existing.is_empty() && !existing.ends_with('\n') {
+                        existing.push('\n');
+                    }
+                    existing.push_str(&text);
+                    text = existing.clone();
+                }
+                ConflictPolicy::Overwrite => {}
+            }
+        }
+        saved_files.save(&id, &text)?;
+        report.imported.push(id);
+    }
+    report.imported.sort_by_key(|x| x.date);
+    report.conflicts.sort_by_key(|x| x.date);
+    Ok(report)
+}
+
+// state of the Import menu
+#[derive(Debug, Default)]
+pub struct ImportDialog {
+    dir: String,
+    policy: ConflictPolicy,
+    status: Option<String>,
+}
+
+impl ImportDialog {
+    // returns true if any notes were written
+    pub fn show(&mut self, ui: &mut egui::Ui, saved_files: &mut SavedFiles) -> bool {
+        let mut imported = false;
+        ui.label("Folder of YYYY-MM-DD.md daily notes (Obsidian, Logseq journals)");
+        ui.text_edit_singleline(&mut self.dir);
+        for policy in ConflictPolicy::ALL {
+            ui.radio_value(&mut self.policy, policy, policy.name());
+        }
+        if ui.button("Import").clicked() {
+            self.status = Some(match import_dir(saved_files, Path::new(self.dir.trim()), self.policy) {
+                Ok(report) => {
+                    imported = !report.imported.is_empty();
+                    report.summary()
+                }
+                Err(e) => e.to_string(),
+            });
+        }
+        if let Some(status) = &self.status {
+            ui.small(status);
+        }
+        imported
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkboxes() {
+        assert_eq!(convert_line("- [ ] open"), "[ ] open");
+        assert_eq!(convert_line("* [x] done"), "[/] done");
+        assert_eq!(convert_line("- [X] done"), "[/] done");
+        assert_eq!(convert_line("- [-] dropped"), "[x] dropped");
+        assert_eq!(convert_line("  - [>] later"), "  [>] later");
+    }
+
+    #[test]
+    fn logseq_markers() {
+        assert_eq!(convert_line("- TODO write"), "[ ] write");
+        assert_eq!(convert_line("- LATER write"), "[ ] write");
+        assert_eq!(convert_line("\t- DOING write"), "\t[~] write");
+        assert_eq!(convert_line("- WAITING reply"), "[!] reply");
+        assert_eq!(convert_line("- DONE write"), "[/] write");
+        assert_eq!(convert_line("- CANCELED write"), "[x] write");
+    }
+
+    #[test]
+    fn other_lines_are_kept() {
+        assert_eq!(convert_line("- plain item"), "- plain item");
+        assert_eq!(convert_line("TODO not a list item"), "TODO not a list item");
+        assert_eq!(convert_line("# Header"), "# Header");
+    }
+
+    #[test]
+    fn dates_from_filenames() {
+        let day = BufferId::parse("2026-10-17");
+        assert_eq!(date_from_filename(Path::new("journals/2026-10-17.md")), day);
+        assert_eq!(date_from_filename(Path::new("journals/2026_10_17.md")), day);
+        assert_eq!(date_from_filename(Path::new("2026.10.17.md")), day);
+        assert_eq!(date_from_filename(Path::new("2026-10-17.txt")), None);
+        assert_eq!(date_from_filename(Path::new("ideas.md")), None);
+        assert_eq!(date_from_filename(Path::new("today.md")), None);
+        assert_eq!(date_from_filename(Path::new("2026-W42.md")), None);
+    }
+}
@@ -0,0 +1,4 @@
+// The highlighter is a library as well so the benchmarks can use it without the rest of the app.
+pub mod style;
+pub mod syntax;
+pub mod theme;
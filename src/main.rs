@@ -1,45 +1,111 @@
 #![windows_subsystem = "windows"]
 
+mod carry_over;
+mod cli;
+mod diff;
+mod export;
+mod git;
+mod html;
+mod import;
 mod note_tree;
-mod style;
+mod rollup;
+mod search;
+mod settings;
+mod tags;
+mod tasks;
+#[cfg(test)]
+mod testing;
 mod update;
 
 use std::{
+    collections::HashMap,
     env,
     fs::File,
     io::{Read, Write},
     ops::Sub,
     path::PathBuf,
+    thread::JoinHandle,
+    time::SystemTime,
 };
 
-use chrono::{Date, Datelike, Local, LocalResult, TimeZone};
+use carry_over::CarryOver;
+use chrono::{Date, Datelike, Local, LocalResult, NaiveDate, TimeZone};
+use diff::{diff_lines, DiffLine};
 use directories::ProjectDirs;
+use export::ExportDialog;
+use import::ImportDialog;
 use eframe::{
-    egui::{self, text_edit::CursorRange, Event, Key, Layout, TextEdit},
+    egui::{
+        self,
+        text_edit::{CCursorRange, CursorRange},
+        Align, Align2, Event, FontFamily, FontId, Key, Layout, TextEdit,
+    },
+    epaint::text::cursor::CCursor,
     epi,
 };
-use note_tree::show_note_tree;
-use regex::Regex;
+use git::{GitBackend, Revision};
+use note_tree::NoteTree;
+use search::Search;
+use tags::TagFilter;
+use settings::Settings;
 use style::CachedLayoutJobBuilder;
+use sunrise::{style, theme};
+use tasks::{show_tasks, show_upcoming, Task, TaskAction, TaskStatus};
+use theme::Theme;
 use update::{current_version, UpdateService, UpdateServiceState};
 use walkdir::WalkDir;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NoteKind {
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct BufferId {
+    // first day of the period the note covers
     date: Date<Local>,
+    kind: NoteKind,
 }
 
 impl Default for BufferId {
     fn default() -> Self {
         Self {
             date: Local::now().date(),
+            kind: NoteKind::Day,
         }
     }
 }
 
+fn local_date(d: NaiveDate) -> Option<Date<Local>> {
+    match Local.from_local_date(&d) {
+        LocalResult::Single(d) => Some(d),
+        _ => None,
+    }
+}
+
 impl BufferId {
     fn new(date: Date<Local>) -> Self {
-        Self { date }
+        Self {
+            date,
+            kind: NoteKind::Day,
+        }
+    }
+
+    // weeks start on monday
+    fn week_of(date: Date<Local>) -> Self {
+        Self {
+            date: date.sub(chrono::Duration::days(date.weekday().num_days_from_monday() as i64)),
+            kind: NoteKind::Week,
+        }
+    }
+
+    fn month_of(date: Date<Local>) -> Self {
+        Self {
+            date: date.with_day(1).unwrap(),
+            kind: NoteKind::Month,
+        }
     }
 
     fn today() -> Self {
@@ -47,63 +113,211 @@ impl BufferId {
     }
 
     fn yesterday() -> Self {
-        Self {
-            date: Local::now().date().sub(chrono::Duration::days(1)),
-        }
+        Self::new(Local::now().date().sub(chrono::Duration::days(1)))
     }
 
     fn prev(&self) -> Self {
-        Self {
-            date: self.date.sub(chrono::Duration::days(1)),
+        Self::new(self.date.sub(chrono::Duration::days(1)))
+    }
+
+    fn is_day(&self) -> bool {
+        self.kind == NoteKind::Day
+    }
+
+    // last day covered by the note
+    fn end(&self) -> Date<Local> {
+        match self.kind {
+            NoteKind::Day => self.date,
+            NoteKind::Week => self.date + chrono::Duration::days(6),
+            NoteKind::Month => {
+                let (year, month) = if self.date.month() == 12 {
+                    (self.date.year() + 1, 1)
+                } else {
+                    (self.date.year(), self.date.month() + 1)
+                };
+                Local.ymd(year, month, 1).pred()
+            }
         }
     }
 
+    fn title(&self) -> String {
+        match self.kind {
+            NoteKind::Day => self.date.format("%Y-%m-%d").to_string(),
+            NoteKind::Week => format!("Week {}, {}", self.date.iso_week().week(), self.date.iso_week().year()),
+            NoteKind::Month => self.date.format("%B %Y").to_string(),
+        }
+    }
+
+    // accepts "today", "yesterday", a YYYY-MM-DD date, a YYYY-Www week or a YYYY-MM month
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "today" => Some(Self::today()),
+            "yesterday" => Some(Self::yesterday()),
+            _ => {
+                if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+                    return local_date(d).map(Self::new);
+                }
+                if let Some((year, week)) = s.split_once("-W") {
+                    let d = NaiveDate::from_isoywd_opt(year.parse().ok()?, week.parse().ok()?, chrono::Weekday::Mon)?;
+                    return local_date(d).map(Self::week_of);
+                }
+                let d = NaiveDate::parse_from_str(&format!("{}-01", s), "%Y-%m-%d").ok()?;
+                local_date(d).map(Self::month_of)
+            }
+        }
+    }
+
+    // days are year/month/day, weeks year/week/number and months year/month/month
     fn filepath(&self) -> PathBuf {
         let dt = self.date;
         let mut path = PathBuf::new();
-        path.push(dt.year().to_string());
-        path.push(dt.month().to_string());
-        path.push(dt.day().to_string());
+        match self.kind {
+            NoteKind::Day => {
+                path.push(dt.year().to_string());
+                path.push(dt.month().to_string());
+                path.push(dt.day().to_string());
+            }
+            NoteKind::Week => {
+                path.push(dt.iso_week().year().to_string());
+                path.push("week");
+                path.push(dt.iso_week().week().to_string());
+            }
+            NoteKind::Month => {
+                path.push(dt.year().to_string());
+                path.push(dt.month().to_string());
+                path.push("month");
+            }
+        }
         path
     }
 }
 
 #[derive(Debug, Default)]
-struct SavedFiles {}
+struct SavedFiles {
+    root: Option<PathBuf>,
+    carry_over: CarryOver,
+}
 
 impl SavedFiles {
+    fn new(settings: &Settings) -> Self {
+        Self {
+            root: settings.notes_dir.clone(),
+            carry_over: settings.carry_over,
+        }
+    }
+
     fn root_dir(&self) -> PathBuf {
-        if let Some(project_dirs) = ProjectDirs::from("com", "marschium", "sunrise") {
+        if let Some(root) = &self.root {
+            root.clone()
+        } else if let Some(project_dirs) = ProjectDirs::from("com", "marschium", "sunrise") {
             project_dirs.data_dir().into()
         } else {
             ".".into()
         }
     }
 
+    // writes to a temporary file first so a failed write can't destroy the note,
+    // the previous version is kept alongside as a .bak
     fn save(&self, id: &BufferId, buf: &String) -> Result<(), std::io::Error> {
         let mut path = self.root_dir();
         path.push(id.filepath());
         std::fs::create_dir_all(path.parent().unwrap())?;
-        match File::create(path) {
+        let tmp_path = path.with_extension("tmp");
+        match File::create(&tmp_path) {
             Ok(mut f) => {
                 f.write_all(&buf.as_bytes())?;
-                Ok(())
+                f.sync_all()?;
             }
-            Err(e) => Err(e),
+            Err(e) => return Err(e),
+        }
+        if path.exists() {
+            std::fs::copy(&path, path.with_extension("bak"))?;
         }
+        std::fs::rename(&tmp_path, &path)
     }
 
     fn load(&mut self, id: &BufferId, buf: &mut String) -> Result<(), std::io::Error> {
         let mut path = self.root_dir();
         path.push(id.filepath());
-        match File::open(path) {
-            Ok(mut f) => {
-                buf.clear();
-                f.read_to_string(buf)?;
-                Ok(())
+        // read into a new string so a failed read leaves 'buf' as it was
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+        *buf = text;
+        Ok(())
+    }
+
+    // every note saved, including weekly and monthly ones
+    fn list(&self) -> Vec<BufferId> {
+        let mut ids = Vec::new();
+        for entry in WalkDir::new(self.root_dir())
+            .into_iter()
+            // .git and the like are never notes, and walking them gets slow
+            .filter_entry(|e| e.depth() == 0 || !e.file_name().to_str().is_some_and(|x| x.starts_with('.')))
+            .filter_map(|e| e.ok())
+        {
+            if entry.path().is_file() {
+                let mut components = entry.path().components().into_iter().rev();
+                let mut next = || components.next().and_then(|x| x.as_os_str().to_str()).unwrap_or("");
+                let (last, middle, year) = (next(), next(), next());
+                let year = match year.parse::<i32>() {
+                    Ok(year) => year,
+                    _ => continue,
+                };
+
+                let id = match (middle, last) {
+                    ("week", week) => week
+                        .parse::<u32>()
+                        .ok()
+                        .and_then(|week| NaiveDate::from_isoywd_opt(year, week, chrono::Weekday::Mon))
+                        .and_then(local_date)
+                        .map(BufferId::week_of),
+                    (month, "month") => month
+                        .parse::<u32>()
+                        .ok()
+                        .and_then(|month| NaiveDate::from_ymd_opt(year, month, 1))
+                        .and_then(local_date)
+                        .map(BufferId::month_of),
+                    (month, day) => match (month.parse::<u32>(), day.parse::<u32>()) {
+                        (Ok(month), Ok(day)) => NaiveDate::from_ymd_opt(year, month, day)
+                            .and_then(local_date)
+                            .map(BufferId::new),
+                        _ => None,
+                    },
+                };
+                if let Some(id) = id {
+                    ids.push(id);
+                }
             }
-            Err(e) => Err(e),
         }
+        ids
+    }
+
+    // calls 'f' with each note in 'buffers' and the open one, whose text comes from the editor as it may not
+    // have been saved yet, notes that can't be read are skipped
+    fn for_each_note(&mut self, buffers: &[BufferId], current: (&BufferId, &str), newest_first: bool, mut f: impl FnMut(&BufferId, &str)) {
+        let mut ids = buffers.to_vec();
+        if !ids.contains(current.0) {
+            ids.push(*current.0);
+        }
+        ids.sort_by_key(|x| x.date);
+        if newest_first {
+            ids.reverse();
+        }
+
+        let mut buf = String::new();
+        for id in ids {
+            if id == *current.0 {
+                f(&id, current.1);
+            } else if self.load(&id, &mut buf).is_ok() {
+                f(&id, &buf);
+            }
+        }
+    }
+
+    fn modified(&self, id: &BufferId) -> Option<SystemTime> {
+        let mut path = self.root_dir();
+        path.push(id.filepath());
+        std::fs::metadata(path).and_then(|x| x.modified()).ok()
     }
 
     fn has(&self, id: &BufferId) -> bool {
@@ -111,8 +325,58 @@ impl SavedFiles {
         path.push(id.filepath());
         path.exists()
     }
+
+    // load today's note, carrying over the most recent previous note into it if today doesn't exist yet
+    fn load_today(&mut self, buf: &mut String) -> Result<(), std::io::Error> {
+        let today = BufferId::today();
+        if self.has(&today) {
+            return self.load(&today, buf);
+        }
+
+        let mut id = BufferId::yesterday();
+        let mut i = 0;
+        while !self.has(&id) && i < 14 {
+            id = id.prev();
+            i += 1;
+        }
+
+        buf.clear();
+        let carried = self.has(&id);
+        if carried {
+            self.load(&id, buf)?;
+            *buf = self.carry_over.apply(buf);
+        }
+        // scheduled tasks that are due but weren't carried over
+        if tasks::insert_due(self, &today, buf) || carried {
+            self.save(&today, buf)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+enum SidePanelView {
+    #[default]
+    Notes,
+    Search,
+    Tasks,
+    Upcoming,
+    Tags,
+}
+
+// the open note was changed on disk while it also had unsaved edits
+struct Conflict {
+    theirs: String,
+    modified: Option<SystemTime>,
+    // from the file to the buffer, kept up to date as the buffer is edited
+    diff: Vec<DiffLine>,
 }
 
+// how long to wait after the last edit before committing to git
+const GIT_IDLE_SECONDS: i64 = 60;
+// how often an idle window wakes up to look for changes on disk and pending saves
+const WATCH_SECONDS: u64 = 2;
+
 struct MyEguiApp {
     buffer_id: BufferId,
     buffer: String,
@@ -123,7 +387,38 @@ struct MyEguiApp {
     cursor: Option<CursorRange>,
     last_changed: Option<chrono::DateTime<Local>>,
     highlight_cache: CachedLayoutJobBuilder,
-    scaled: bool
+    progress_cache: tasks::ProgressCache,
+    settings: Settings,
+    // settings edited in the open menu, written once it closes
+    settings_changed: bool,
+    // built-in themes followed by the user's
+    themes: Vec<Theme>,
+    notes_dir_edit: String,
+    // the remote is only stored once the field loses focus
+    git_remote_edit: String,
+    side_panel: SidePanelView,
+    search: Search,
+    tasks: Option<Vec<Task>>,
+    tag_filter: TagFilter,
+    open_task_counts: Option<HashMap<BufferId, usize>>,
+    note_tree: NoteTree,
+    export: ExportDialog,
+    import: ImportDialog,
+    goto_date: Option<String>,
+    save_error: Option<String>,
+    // the open note couldn't be read, it's shown empty and read-only so nothing is saved over it
+    load_error: Option<String>,
+    // the last failure that isn't about saving the note, shown until dismissed
+    error: Option<String>,
+    loaded_modified: Option<SystemTime>,
+    conflict: Option<Conflict>,
+    uncommitted: bool,
+    // the background commit and sync, if one is running
+    git_worker: Option<JoinHandle<Result<(), std::io::Error>>>,
+    history: Option<Vec<Revision>>,
+    scroll_to_cursor: bool,
+    // Tasks panel lists closed tasks instead of open ones
+    show_closed_tasks: bool,
 }
 
 impl Default for MyEguiApp {
@@ -138,32 +433,50 @@ impl Default for MyEguiApp {
             cursor: Default::default(),
             last_changed: Default::default(),
             highlight_cache: Default::default(),
-            scaled: false
+            progress_cache: Default::default(),
+            settings: Default::default(),
+            settings_changed: false,
+            themes: vec![Theme::dark(), Theme::light()],
+            notes_dir_edit: Default::default(),
+            git_remote_edit: Default::default(),
+            side_panel: Default::default(),
+            search: Default::default(),
+            tasks: Default::default(),
+            tag_filter: Default::default(),
+            open_task_counts: Default::default(),
+            note_tree: Default::default(),
+            export: Default::default(),
+            import: Default::default(),
+            goto_date: Default::default(),
+            save_error: Default::default(),
+            load_error: Default::default(),
+            error: Default::default(),
+            loaded_modified: Default::default(),
+            conflict: Default::default(),
+            uncommitted: false,
+            git_worker: None,
+            history: Default::default(),
+            scroll_to_cursor: false,
+            show_closed_tasks: false,
         }
     }
 }
 
 impl MyEguiApp {
-    pub fn load(demo: bool) -> Self {
-        //
-
-        let mut s = Self::default();
-        let copy_from_previous = !s.saved_files.has(&BufferId::today());
-        if copy_from_previous {
-            let mut id = BufferId::yesterday();
-            let mut i = 0;
-            while !s.saved_files.has(&id) && i < 14 {
-                id = id.prev();
-                i += 1;
-            }
-
-            if s.saved_files.has(&id) {
-                let _ = s.saved_files.load(&id, &mut s.buffer);
-                let _ = s.saved_files.save(&BufferId::today(), &s.buffer);
-            }
-        } else {
-            let _ = s.saved_files.load(&BufferId::today(), &mut s.buffer);
+    pub fn load(demo: bool, settings: Settings, notes_dir: Option<PathBuf>) -> Self {
+        let mut s = Self {
+            saved_files: SavedFiles::new(&settings),
+            ..Default::default()
+        };
+        if notes_dir.is_some() {
+            // command line override, not persisted
+            s.saved_files.root = notes_dir;
         }
+        s.notes_dir_edit = s.saved_files.root_dir().to_string_lossy().into_owned();
+        s.git_remote_edit = settings.git_remote.clone();
+        s.settings = settings;
+        s.themes = Theme::load_all(&settings::themes_dir());
+        s.load_buffer();
 
         s.update_available_buffers();
         if demo {
@@ -183,87 +496,525 @@ https://google.com/about.html?arg=hello%20world
     }
 
     fn update_available_buffers(&mut self) {
-        self.available_buffers.clear();
-        for entry in WalkDir::new(self.saved_files.root_dir())
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            if entry.path().is_file() {
-                let mut components = entry.path().components().into_iter().rev();
-                let day = components
-                    .next()
-                    .and_then(|x| x.as_os_str().to_str())
-                    .and_then(|x| x.parse::<u32>().ok());
-                let month = components
-                    .next()
-                    .and_then(|x| x.as_os_str().to_str())
-                    .and_then(|x| x.parse::<u32>().ok());
-                let year = components
-                    .next()
-                    .and_then(|x| x.as_os_str().to_str())
-                    .and_then(|x| x.parse::<i32>().ok());
-
-                match (year, month, day) {
-                    (Some(year), Some(month), Some(day)) => {
-                        let d = Local.ymd_opt(year, month, day);
-                        match d {
-                            LocalResult::Single(d) => {
-                                self.available_buffers.push(BufferId::new(d));
-                            }
-                            _ => {}
-                        }
+        self.available_buffers = self.saved_files.list();
+    }
+
+    fn git(&self) -> Option<GitBackend> {
+        GitBackend::from_settings(&self.settings, self.saved_files.root_dir())
+    }
+
+    // commit and push in the background so a slow remote doesn't block the ui, one run at a time
+    fn commit_notes(&mut self, ctx: &egui::Context) {
+        if self.git_worker.is_some() {
+            // tried again once the running one is done
+            return;
+        }
+        self.uncommitted = false;
+        if let Some(git) = self.git() {
+            let ctx = ctx.clone();
+            self.git_worker = Some(std::thread::spawn(move || {
+                let result = git
+                    .commit_all(&format!("Notes {}", Local::now().format("%Y-%m-%d %H:%M")))
+                    .and_then(|committed| if committed { git.sync() } else { Ok(()) });
+                ctx.request_repaint();
+                result
+            }));
+        }
+    }
+
+    // picks up the result of a finished commit, pulling from the remote may have changed the open note
+    fn finish_git_worker(&mut self) {
+        if !self.git_worker.as_ref().is_some_and(|x| x.is_finished()) {
+            return;
+        }
+        match self.git_worker.take().unwrap().join() {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => self.error = Some(e.to_string()),
+            Err(_) => self.error = Some("git stopped unexpectedly".to_owned()),
+        }
+        self.history = None;
+        self.check_external_changes();
+        self.update_available_buffers();
+    }
+
+    fn show_history(&mut self, ui: &mut egui::Ui) {
+        let git = match self.git() {
+            Some(git) => git,
+            None => return,
+        };
+        let path = self.buffer_id.filepath();
+        if self.history.is_none() {
+            match git.history(&path) {
+                Ok(history) => self.history = Some(history),
+                Err(e) => {
+                    self.history = Some(Vec::new());
+                    self.error = Some(e.to_string());
+                }
+            }
+        }
+        let history = self.history.as_ref().unwrap();
+        if history.is_empty() {
+            ui.label("No history yet");
+        }
+
+        let mut restored = None;
+        egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            for revision in history.iter() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} {}", revision.date, revision.hash))
+                        .on_hover_text(&revision.summary);
+                    if ui.small_button("Restore").clicked() {
+                        restored = Some(git.show(revision, &path));
                     }
-                    _ => {}
+                });
+            }
+        });
+
+        match restored {
+            Some(Ok(text)) => {
+                // an old version replaces a note that can't be read
+                self.load_error = None;
+                self.buffer = text;
+                self.saved = false;
+                self.last_changed = Some(Local::now());
+                ui.close_menu();
+            }
+            Some(Err(e)) => self.error = Some(e.to_string()),
+            None => {}
+        }
+    }
+
+    fn save_buffer(&mut self) -> bool {
+        if self.conflict.is_some() || self.load_error.is_some() {
+            // has to be resolved first
+            return false;
+        }
+        if self.buffer.is_empty() && !self.saved_files.has(&self.buffer_id) {
+            // a day that was only looked at doesn't get a file
+            self.saved = true;
+            return true;
+        }
+        self.invalidate_notes();
+        match self.saved_files.save(&self.buffer_id, &self.buffer) {
+            Ok(_) => {
+                self.saved = true;
+                self.uncommitted = true;
+                self.save_error = None;
+                self.loaded_modified = self.saved_files.modified(&self.buffer_id);
+                if !self.available_buffers.contains(&self.buffer_id) {
+                    self.update_available_buffers();
                 }
+                true
+            }
+            Err(e) => {
+                // try again after the autosave delay
+                self.saved = false;
+                self.last_changed = Some(Local::now());
+                self.save_error = Some(e.to_string());
+                false
             }
         }
     }
 
+    // reads the note for 'buffer_id' into the editor
+    fn load_buffer(&mut self) {
+        let loaded = if self.buffer_id == BufferId::today() {
+            let loaded = self.saved_files.load_today(&mut self.buffer);
+            self.update_available_buffers();
+            loaded
+        } else if self.saved_files.has(&self.buffer_id) {
+            self.saved_files.load(&self.buffer_id, &mut self.buffer)
+        } else {
+            // the note is created once something is written in it
+            self.buffer.clear();
+            Ok(())
+        };
+        self.saved = true;
+        self.load_error = match loaded {
+            Ok(()) => None,
+            Err(e) => {
+                self.buffer.clear();
+                Some(e.to_string())
+            }
+        };
+        // a note that couldn't be read is tried again once it changes on disk
+        self.loaded_modified = self.saved_files.modified(&self.buffer_id);
+    }
+
     fn swap_to_buffer(&mut self, id: &BufferId) {
-        let _ = self.saved_files.save(&self.buffer_id, &self.buffer);
+        if !self.saved && self.load_error.is_none() && !self.save_buffer() {
+            // stay on the current note rather than losing the edits
+            return;
+        }
         self.buffer_id = *id;
-        let _ = self.saved_files.load(&self.buffer_id, &mut self.buffer);
-    }
-
-    // replace first occurance of 'find' on the current line
-    fn replace_on_current_line(&mut self, start_pos: usize, find: &str, replace: &str) -> bool {
-        let substr = self.buffer.get(0..start_pos).unwrap();
-        let search_start = substr.rfind("\n").unwrap_or(0);
-        let substr = self.buffer.get(search_start..start_pos).unwrap();
-        match substr.rfind(find) {
-            Some(i) => {
-                self.buffer
-                    .replace_range(i + search_start..i + search_start + find.len(), replace);
-                true
+        self.note_tree.show_month_of(id);
+        self.load_buffer();
+
+        if !self.buffer_id.is_day() && self.load_error.is_none() {
+            let summary = rollup::summary(&mut self.saved_files, &self.available_buffers, &self.buffer_id);
+            let updated = rollup::with_summary(&self.buffer, &self.buffer_id, &summary);
+            if updated != self.buffer {
+                // only a note that already exists is rewritten, a new one is written once it's edited
+                if self.saved_files.has(&self.buffer_id) {
+                    self.saved = false;
+                    self.last_changed = Some(Local::now());
+                }
+                self.buffer = updated;
             }
-            None => false,
         }
     }
 
-    fn replace_task_for_cursor(&mut self, cursor_pos: usize) {
-        let mut changed = self.replace_on_current_line(cursor_pos, "[x]", "[/]");
-        if !changed {
-            changed = self.replace_on_current_line(cursor_pos, "[/]", "[x]");
+    // reload the open note if another program changed it, unless there are edits that would be lost
+    fn check_external_changes(&mut self) {
+        let modified = self.saved_files.modified(&self.buffer_id);
+        if modified.is_none() || modified == self.loaded_modified || self.conflict.is_some() {
+            return;
+        }
+
+        let mut theirs = String::new();
+        if self.saved_files.load(&self.buffer_id, &mut theirs).is_err() {
+            return;
+        }
+        // it can be read now
+        self.load_error = None;
+        if theirs == self.buffer {
+            self.loaded_modified = modified;
+        } else if self.saved {
+            self.buffer = theirs;
+            self.loaded_modified = modified;
+            self.invalidate_notes();
+        } else {
+            let diff = diff_lines(&theirs, &self.buffer);
+            self.conflict = Some(Conflict { theirs, modified, diff });
+        }
+    }
+
+    fn show_conflict(&mut self, ctx: &egui::Context) {
+        let conflict = match &self.conflict {
+            Some(conflict) => conflict,
+            None => return,
+        };
+        let theme = self.theme();
+
+        let mut keep_mine = false;
+        let mut use_theirs = false;
+        egui::Window::new("Note changed on disk")
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label("This note was changed by another program while it had unsaved edits.");
+                ui.small("- only on disk, + only in sunrise");
+                ui.horizontal(|ui| {
+                    keep_mine = ui.button("Keep mine").clicked();
+                    use_theirs = ui.button("Use theirs").clicked();
+                });
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for line in &conflict.diff {
+                        match line {
+                            DiffLine::Same(l) => ui.monospace(format!("  {}", l)),
+                            DiffLine::Added(l) => ui.colored_label(theme.added, format!("+ {}", l)),
+                            DiffLine::Removed(l) => ui.colored_label(theme.removed, format!("- {}", l)),
+                        };
+                    }
+                });
+            });
+
+        if keep_mine {
+            self.loaded_modified = self.conflict.take().and_then(|x| x.modified);
+            self.save_buffer();
+        } else if use_theirs {
+            let conflict = self.conflict.take().unwrap();
+            self.buffer = conflict.theirs;
+            self.loaded_modified = conflict.modified;
+            self.saved = true;
+            self.invalidate_notes();
+        }
+    }
+
+    // cached views of the notes are rebuilt the next time they're shown
+    fn invalidate_notes(&mut self) {
+        self.tasks = None;
+        self.open_task_counts = None;
+        self.tag_filter.invalidate();
+    }
+
+    // closest day before or after the open one that has a note
+    fn neighbouring_buffer(&self, forward: bool) -> Option<BufferId> {
+        let current = self.buffer_id.date;
+        if forward {
+            self.available_buffers.iter().filter(|x| x.is_day() && x.date > current).min_by_key(|x| x.date).copied()
+        } else {
+            self.available_buffers.iter().filter(|x| x.is_day() && x.date < current).max_by_key(|x| x.date).copied()
         }
-        if !changed {
-            changed = self.replace_on_current_line(cursor_pos, "[]", "[ ]");
+    }
+
+    fn show_goto_date(&mut self, ctx: &egui::Context) {
+        let mut selected = None;
+        let mut close = false;
+        if let Some(text) = &mut self.goto_date {
+            egui::Window::new("Go to date")
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.text_edit_singleline(text).request_focus();
+                    let id = BufferId::parse(text.trim());
+                    if id.is_none() && !text.is_empty() {
+                        ui.small("today, yesterday, YYYY-MM-DD, YYYY-Www or YYYY-MM");
+                    }
+                    if ui.input().key_pressed(Key::Enter) {
+                        selected = id;
+                    }
+                    close = ui.input().key_pressed(Key::Escape);
+                });
         }
-        if !changed {
-            changed = self.replace_on_current_line(cursor_pos, "[ ]", "[/]");
+
+        if let Some(id) = selected {
+            self.goto_date = None;
+            self.swap_to_buffer(&id);
+        } else if close {
+            self.goto_date = None;
         }
-        if !changed {
-            let substr = self.buffer.get(0..cursor_pos).unwrap();
-            let end_of_prev_line = substr.rfind("\n").unwrap_or(0);
-            self.buffer.insert_str(
-                if end_of_prev_line > 0 {
-                    end_of_prev_line + 1
+    }
+
+    fn apply_task_action(&mut self, action: TaskAction) {
+        match action {
+            TaskAction::Open(id) => self.swap_to_buffer(&id),
+            TaskAction::SetStatus(task, status) => {
+                if task.last_seen == self.buffer_id {
+                    if tasks::set_status(&mut self.buffer, &task.text, task.status, status, self.settings.stamp_completion) {
+                        self.saved = false;
+                        self.last_changed = Some(Local::now());
+                    }
                 } else {
-                    0
-                },
-                "[ ] ",
-            );
+                    let mut buf = String::new();
+                    let changed = self
+                        .saved_files
+                        .load(&task.last_seen, &mut buf)
+                        .map(|_| tasks::set_status(&mut buf, &task.text, task.status, status, self.settings.stamp_completion));
+                    let saved = match changed {
+                        Ok(true) => self.saved_files.save(&task.last_seen, &buf).map(|_| self.uncommitted = true),
+                        other => other.map(|_| ()),
+                    };
+                    if let Err(e) = saved {
+                        self.error = Some(format!("Couldn't update {}: {}", task.last_seen.title(), e));
+                    }
+                }
+                self.invalidate_notes();
+            }
         }
     }
+
+    fn editor_id() -> egui::Id {
+        egui::Id::new("editor")
+    }
+
+    // select a char range in the editor and scroll it into view on the next frame
+    fn select_range(&mut self, ctx: &egui::Context, start: usize, end: usize) {
+        let mut state = TextEdit::load_state(ctx, Self::editor_id()).unwrap_or_default();
+        state.set_ccursor_range(Some(CCursorRange::two(CCursor::new(start), CCursor::new(end))));
+        TextEdit::store_state(ctx, Self::editor_id(), state);
+        ctx.memory().request_focus(Self::editor_id());
+        self.scroll_to_cursor = true;
+    }
+
+    fn show_settings(&mut self, ui: &mut egui::Ui) {
+        let before = self.settings.clone();
+
+        let mut old_man_mode = self.settings.ui_scale >= 1.8;
+        if ui.checkbox(&mut old_man_mode, "Old Man Mode").changed() {
+            self.settings.ui_scale = if old_man_mode { 1.8 } else { 1.2 };
+        }
+        ui.add(egui::Slider::new(&mut self.settings.ui_scale, 0.8..=3.0).text("UI scale"));
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("Theme")
+                .selected_text(self.settings.theme.clone())
+                .show_ui(ui, |ui| {
+                    for theme in &self.themes {
+                        ui.selectable_value(&mut self.settings.theme, theme.name.clone(), &theme.name);
+                    }
+                });
+            if ui.small_button("⟳").on_hover_text("Reload theme files").clicked() {
+                self.themes = Theme::load_all(&settings::themes_dir());
+            }
+        });
+        ui.add(egui::Slider::new(&mut self.settings.autosave_delay, 1..=60).text("Autosave delay (s)"));
+        ui.checkbox(&mut self.settings.stamp_completion, "Date tasks when they're completed");
+
+        ui.separator();
+        ui.label("Ctrl+M cycles through");
+        ui.horizontal_wrapped(|ui| {
+            for status in TaskStatus::ALL {
+                let mut included = self.settings.task_cycle.contains(&status);
+                if ui.checkbox(&mut included, status.marker()).on_hover_text(status.name()).changed() {
+                    if included {
+                        self.settings.task_cycle.push(status);
+                    } else if self.settings.task_cycle.len() > 1 {
+                        self.settings.task_cycle.retain(|x| *x != status);
+                    }
+                }
+            }
+        });
+        ui.horizontal_wrapped(|ui| {
+            let mut move_earlier = None;
+            for (i, status) in self.settings.task_cycle.iter().enumerate() {
+                if i > 0 && ui.small_button("⏴").on_hover_text("Move earlier").clicked() {
+                    move_earlier = Some(i);
+                }
+                ui.monospace(status.marker());
+            }
+            if let Some(i) = move_earlier {
+                self.settings.task_cycle.swap(i - 1, i);
+            }
+        });
+
+        ui.separator();
+        ui.label("Carry over to new days");
+        for policy in CarryOver::ALL {
+            ui.radio_value(&mut self.settings.carry_over, policy, policy.name());
+        }
+
+        ui.separator();
+        ui.checkbox(&mut self.settings.git_enabled, "Keep history in git");
+        ui.horizontal(|ui| {
+            ui.label("Remote");
+            if ui.text_edit_singleline(&mut self.git_remote_edit).lost_focus() {
+                self.settings.git_remote = self.git_remote_edit.trim().to_owned();
+            }
+        });
+
+        ui.separator();
+        ui.label("Notes directory");
+        ui.text_edit_singleline(&mut self.notes_dir_edit);
+        if ui.button("Apply").clicked() && (self.saved || self.save_buffer()) {
+            self.settings.notes_dir = Some(self.notes_dir_edit.clone().into());
+            self.saved_files.root = self.settings.notes_dir.clone();
+            self.buffer_id = BufferId::today();
+            self.load_buffer();
+            self.save_error = None;
+            self.update_available_buffers();
+        }
+
+        if self.settings != before {
+            self.saved_files.carry_over = self.settings.carry_over;
+            self.settings_changed = true;
+        }
+    }
+
+    // writing on every change would rewrite the file each frame a slider is dragged
+    fn save_settings(&mut self) {
+        if !self.settings_changed {
+            return;
+        }
+        self.settings_changed = false;
+        if let Err(e) = self.settings.save() {
+            self.error = Some(format!("Settings not saved: {}", e));
+        }
+    }
+
+    // the selected theme, or the first one if its file has gone
+    fn theme(&self) -> &Theme {
+        self.themes.iter().find(|x| x.name == self.settings.theme).unwrap_or(&self.themes[0])
+    }
+
+    // rewrites every line the selection touches, or the cursor's line, and keeps the selection on the
+    // same text afterwards, completion dates are kept in step with the new status
+    fn edit_selected_lines(&mut self, ctx: &egui::Context, f: impl Fn(&str) -> String) {
+        let cursor = match self.cursor {
+            Some(cursor) if self.load_error.is_none() => cursor,
+            _ => return,
+        };
+        let primary = line_col(&self.buffer, cursor.primary.ccursor.index);
+        let secondary = line_col(&self.buffer, cursor.secondary.ccursor.index);
+        let (start, end) = (primary.min(secondary), primary.max(secondary));
+        // a selection of whole lines ends at the start of the next one
+        let last = if end.0 > start.0 && end.1 == 0 { end.0 - 1 } else { end.0 };
+
+        let mut lines: Vec<String> = self.buffer.split('\n').map(|x| x.to_owned()).collect();
+        let old_lens: Vec<usize> = lines.iter().map(|x| x.chars().count()).collect();
+        // only lines whose status changed get a new stamp and close their sub-tasks
+        let mut changed = Vec::new();
+        for (i, line) in lines.iter_mut().enumerate().take(last + 1).skip(start.0) {
+            if last > start.0 && line.trim().is_empty() {
+                continue;
+            }
+            let status = TaskStatus::parse_line(line).map(|x| x.0);
+            *line = f(line);
+            if TaskStatus::parse_line(line).map(|x| x.0) != status {
+                if self.settings.stamp_completion {
+                    *line = tasks::restamp(line, Local::today());
+                }
+                changed.push(i);
+            }
+        }
+        self.buffer = lines.join("\n");
+        for i in changed {
+            tasks::close_subtasks(&mut self.buffer, i, self.settings.stamp_completion);
+        }
+        self.saved = false;
+        self.last_changed = Some(Local::now());
+
+        // edits happen at the start and end of lines, a cursor past the start moves with the text
+        let new_lens: Vec<usize> = self.buffer.split('\n').map(|x| x.chars().count()).collect();
+        let index = |(line, col): (usize, usize)| {
+            let col = if col == 0 { 0 } else { (col + new_lens[line]).saturating_sub(old_lens[line]).min(new_lens[line]) };
+            new_lens[..line].iter().sum::<usize>() + line + col
+        };
+        let mut state = TextEdit::load_state(ctx, Self::editor_id()).unwrap_or_default();
+        let range = CCursorRange::two(CCursor::new(index(secondary)), CCursor::new(index(primary)));
+        state.set_ccursor_range(Some(range));
+        TextEdit::store_state(ctx, Self::editor_id(), state);
+    }
+}
+
+// web links go to the browser, file:// and mailto: to whatever the system opens them with
+fn open_link(url: &str) -> std::io::Result<()> {
+    match style::openable_scheme(url).as_deref() {
+        Some("http" | "https") => webbrowser::open(url),
+        Some(_) => open_with_system(url),
+        None => Err(std::io::Error::other("only http, https, file and mailto links can be opened")),
+    }
+}
+
+// the url is handed over as it is, never through a shell that would read it as a command line
+#[cfg(windows)]
+fn open_with_system(url: &str) -> std::io::Result<()> {
+    use std::{ffi::c_void, ptr};
+    const SW_SHOWNORMAL: i32 = 1;
+    #[link(name = "shell32")]
+    extern "system" {
+        fn ShellExecuteW(
+            hwnd: *mut c_void,
+            operation: *const u16,
+            file: *const u16,
+            parameters: *const u16,
+            directory: *const u16,
+            show_cmd: i32,
+        ) -> isize;
+    }
+    let wide = |s: &str| s.encode_utf16().chain(Some(0)).collect::<Vec<u16>>();
+    let (operation, file) = (wide("open"), wide(url));
+    let result = unsafe { ShellExecuteW(ptr::null_mut(), operation.as_ptr(), file.as_ptr(), ptr::null(), ptr::null(), SW_SHOWNORMAL) };
+    // anything above 32 means it worked
+    if result > 32 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(windows))]
+fn open_with_system(url: &str) -> std::io::Result<()> {
+    let program = if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+    std::process::Command::new(program).arg(url).spawn().map(|_| ())
+}
+
+// line number and column of a char index
+fn line_col(text: &str, index: usize) -> (usize, usize) {
+    let mut pos = (0, 0);
+    for c in text.chars().take(index) {
+        pos = if c == '\n' { (pos.0 + 1, 0) } else { (pos.0, pos.1 + 1) };
+    }
+    pos
 }
 
 impl epi::App for MyEguiApp {
@@ -271,33 +1022,45 @@ impl epi::App for MyEguiApp {
         "sunrise"
     }
 
+    fn setup(&mut self, ctx: &egui::Context, _frame: &epi::Frame, _storage: Option<&dyn epi::Storage>) {
+        // egui only repaints on input, so without this an idle window never notices a sync client
+        let ctx = ctx.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(std::time::Duration::from_secs(WATCH_SECONDS));
+            ctx.request_repaint();
+        });
+    }
+
+    fn on_exit(&mut self) {
+        self.save_settings();
+    }
+
     fn update(&mut self, ctx: &egui::Context, frame: &epi::Frame) {
-        if self.scaled {
-            ctx.set_pixels_per_point(1.8);
-        }
-        else {
-            ctx.set_pixels_per_point(1.2);
-        }
-        ctx.set_visuals(egui::Visuals::dark());
+        ctx.set_pixels_per_point(self.settings.ui_scale);
+        self.theme().apply(ctx);
 
-        if !self.saved {
+        self.finish_git_worker();
+        self.check_external_changes();
+        // a running commit may be rebasing the notes, saving waits for it
+        if !self.saved && self.git_worker.is_none() {
             let recent_edit = match self.last_changed {
-                Some(last_changed) => (Local::now() - last_changed > chrono::Duration::seconds(5)),
+                Some(last_changed) => Local::now() - last_changed > chrono::Duration::seconds(self.settings.autosave_delay as i64),
                 _ => false,
             };
             if recent_edit {
-                self.saved = true;
-                let _ = self.saved_files.save(&self.buffer_id, &self.buffer);
+                self.save_buffer();
+            }
+        } else if self.uncommitted {
+            let idle = match self.last_changed {
+                Some(last_changed) => Local::now() - last_changed > chrono::Duration::seconds(GIT_IDLE_SECONDS),
+                _ => true,
+            };
+            if idle {
+                self.commit_notes(ctx);
             }
         }
 
-        let mut any_key_pressed = false;
         for event in ctx.input().events.clone() {
-            if !any_key_pressed {
-                any_key_pressed = matches!(event, Event::Text(..))
-                    || matches!(event, Event::Paste(..))
-                    || matches!(event, Event::Key { .. });
-            }
             match event {
                 Event::Key {
                     key: Key::M,
@@ -305,12 +1068,40 @@ impl epi::App for MyEguiApp {
                     modifiers,
                 } => {
                     if modifiers.command {
-                        match self.cursor {
-                            Some(cursor) => {
-                                self.replace_task_for_cursor(cursor.primary.ccursor.index);
-                            }
-                            None => {}
-                        }
+                        let cycle = self.settings.task_cycle.clone();
+                        self.edit_selected_lines(ctx, |line| tasks::cycle_line(line, &cycle));
+                    }
+                }
+                Event::Key {
+                    key: key @ (Key::Num1 | Key::Num2 | Key::Num3 | Key::Num4 | Key::Num5 | Key::Num6),
+                    pressed: true,
+                    modifiers,
+                } => {
+                    // Ctrl+1 to Ctrl+6 set a state directly, in the order of TaskStatus::ALL
+                    let status = match key {
+                        Key::Num1 => TaskStatus::Open,
+                        Key::Num2 => TaskStatus::InProgress,
+                        Key::Num3 => TaskStatus::Blocked,
+                        Key::Num4 => TaskStatus::Deferred,
+                        Key::Num5 => TaskStatus::Completed,
+                        _ => TaskStatus::Cancelled,
+                    };
+                    if modifiers.command {
+                        self.edit_selected_lines(ctx, |line| tasks::set_line_status(line, status));
+                    }
+                }
+                Event::Key {
+                    key: Key::D,
+                    pressed: true,
+                    modifiers,
+                } => {
+                    // complete, or with shift cancel, every task in the selection
+                    let status = if modifiers.shift { TaskStatus::Cancelled } else { TaskStatus::Completed };
+                    if modifiers.command {
+                        self.edit_selected_lines(ctx, |line| match TaskStatus::parse_line(line) {
+                            Some(_) => tasks::set_line_status(line, status),
+                            None => line.to_owned(),
+                        });
                     }
                 }
                 Event::Key {
@@ -322,35 +1113,90 @@ impl epi::App for MyEguiApp {
                         self.swap_to_buffer(&BufferId::today());
                     }
                 }
+                Event::Key {
+                    key: key @ (Key::PageUp | Key::PageDown),
+                    pressed: true,
+                    modifiers,
+                } if modifiers.command => {
+                    if let Some(id) = self.neighbouring_buffer(key == Key::PageDown) {
+                        self.swap_to_buffer(&id);
+                    }
+                }
+                Event::Key {
+                    key: Key::G,
+                    pressed: true,
+                    modifiers,
+                } if modifiers.command => {
+                    self.goto_date = Some(String::new());
+                }
                 Event::Key {
                     key: Key::S,
                     pressed: true,
                     modifiers,
                 } => {
                     if modifiers.command {
-                        self.saved = true;
-                        let _ = self.saved_files.save(&self.buffer_id, &self.buffer);
+                        self.save_buffer();
                     }
                 }
                 _ => {}
             }
         }
 
+        self.show_goto_date(ctx);
+        self.show_conflict(ctx);
+
+        let theme = self.theme().clone();
         egui::TopBottomPanel::top("top").show(ctx, |ui| {
 
             ui.horizontal(|ui| {
                 ui.with_layout(egui::Layout::left_to_right(), |ui| {
-                    ui.menu_button("Settings", |ui| {
-                        ui.checkbox(&mut self.scaled, "Old Man Mode");
+                    let settings = ui.menu_button("Settings", |ui| {
+                        self.show_settings(ui);
                     });
-                    let save_status = if self.saved { "Saved" } else { "Not Saved" };
+                    if settings.inner.is_none() {
+                        self.save_settings();
+                    }
+                    ui.menu_button("Export", |ui| {
+                        self.export.show(ui, &mut self.saved_files, &theme);
+                    });
+                    ui.menu_button("Import", |ui| {
+                        if self.import.show(ui, &mut self.saved_files) {
+                            self.update_available_buffers();
+                            self.invalidate_notes();
+                        }
+                    });
+                    if self.settings.git_enabled {
+                        let open = ui.menu_button("History", |ui| {
+                            self.show_history(ui);
+                        });
+                        if open.inner.is_none() {
+                            self.history = None;
+                        }
+                    }
+                    let save_status = match (&self.load_error, &self.save_error) {
+                        (Some(e), _) => format!("Couldn't load, not saving: {}", e),
+                        (_, Some(e)) => format!("Save failed: {}", e),
+                        _ if self.saved => "Saved".to_owned(),
+                        _ => "Not Saved".to_owned(),
+                    };
                     ui.centered_and_justified(|ui| {
-                        let s = self.buffer_id.filepath().to_str().unwrap_or("???").to_owned() + " (" + save_status + ")"; 
-                        ui.label(s);
+                        let s = self.buffer_id.filepath().to_str().unwrap_or("???").to_owned() + " (" + &save_status + ")"; 
+                        if self.save_error.is_some() || self.load_error.is_some() {
+                            ui.colored_label(theme.error, s);
+                        } else {
+                            ui.label(s);
+                        }
                     });
                     ui.with_layout(egui::Layout::right_to_left(), |ui| {
                         ui.add_space(8.0);
                         ui.label(format!("v{}", current_version()));
+                        if let Some(e) = &self.error {
+                            if ui.small_button("✖").on_hover_text("Dismiss").clicked() {
+                                self.error = None;
+                            } else {
+                                ui.colored_label(theme.error, e);
+                            }
+                        }
 
                         let current_update_status = self.update_service.state();
                         match current_update_status {
@@ -368,67 +1214,192 @@ impl epi::App for MyEguiApp {
             });
         });
         egui::SidePanel::left("buffers").show(ctx, |ui| {
-            if let Some(buffer_id) = show_note_tree(&self.available_buffers, ui) {
-                self.swap_to_buffer(&buffer_id);
-                any_key_pressed = true;
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.side_panel, SidePanelView::Notes, "Notes");
+                ui.selectable_value(&mut self.side_panel, SidePanelView::Search, "Search");
+                ui.selectable_value(&mut self.side_panel, SidePanelView::Tasks, "Tasks");
+                ui.selectable_value(&mut self.side_panel, SidePanelView::Upcoming, "Upcoming");
+                ui.selectable_value(&mut self.side_panel, SidePanelView::Tags, "Tags");
+            });
+            ui.separator();
+            match self.side_panel {
+                SidePanelView::Notes => {
+                    if self.open_task_counts.is_none() {
+                        self.open_task_counts = Some(tasks::open_counts(
+                            &mut self.saved_files,
+                            &self.available_buffers,
+                            (&self.buffer_id, &self.buffer),
+                        ));
+                    }
+                    let counts = self.open_task_counts.as_ref().unwrap();
+                    let selected = self.note_tree.show(ui, &self.available_buffers, &self.buffer_id, counts, &theme);
+                    if let Some(buffer_id) = selected {
+                        self.swap_to_buffer(&buffer_id);
+                    }
+                }
+                SidePanelView::Search => {
+                    let hit = self.search.show(
+                        ui,
+                        &mut self.saved_files,
+                        &self.available_buffers,
+                        (&self.buffer_id, &self.buffer),
+                        &theme,
+                    );
+                    if let Some(hit) = hit {
+                        if hit.id != self.buffer_id {
+                            self.swap_to_buffer(&hit.id);
+                        }
+                        self.select_range(ctx, hit.start, hit.end);
+                    }
+                }
+                SidePanelView::Tags => {
+                    let hit = self.tag_filter.show(
+                        ui,
+                        &mut self.saved_files,
+                        &self.available_buffers,
+                        (&self.buffer_id, &self.buffer),
+                    );
+                    if let Some(hit) = hit {
+                        if hit.id != self.buffer_id {
+                            self.swap_to_buffer(&hit.id);
+                        }
+                        self.select_range(ctx, hit.start, hit.end);
+                    }
+                }
+                view @ (SidePanelView::Tasks | SidePanelView::Upcoming) => {
+                    if self.tasks.is_none() {
+                        self.tasks = Some(tasks::collect(
+                            &mut self.saved_files,
+                            &self.available_buffers,
+                            (&self.buffer_id, &self.buffer),
+                        ));
+                    }
+                    let tasks = self.tasks.as_deref().unwrap_or_default();
+                    let action = if view == SidePanelView::Tasks {
+                        show_tasks(ui, tasks, &mut self.show_closed_tasks)
+                    } else {
+                        show_upcoming(ui, tasks, self.theme())
+                    };
+                    if let Some(action) = action {
+                        self.apply_task_action(action);
+                    }
+                }
             }
         });
 
         egui::CentralPanel::default().show(ctx, |ui| {
             let mut layouter = |ui: &egui::Ui, string: &str, wrap_width: f32| {
-                if any_key_pressed {
-                    self.highlight_cache.clear();
-                }
-                // TODO pass the style from the ui into highligther
-                let mut layout_job = self.highlight_cache.highlight(string);
+                let mut layout_job = self.highlight_cache.highlight(string, &theme);
                 layout_job.wrap_width = wrap_width;
                 ui.fonts().layout_job(layout_job)
             };
 
             let mut text_changed = false;
+            // char index of the character under the pointer, and whether it was Ctrl+clicked
+            let mut hovered = None;
+            let mut link_clicked = false;
             let layout = Layout::centered_and_justified(ui.layout().main_dir());
             ui.allocate_ui_with_layout(ui.available_size(), layout, |ui| {
                 egui::ScrollArea::vertical().show(ui, |ui| {
                     let output = TextEdit::multiline(&mut self.buffer)
+                        .id(Self::editor_id())
                         .layouter(&mut layouter)
                         .lock_focus(true)
+                        .interactive(self.load_error.is_none())
                         .show(ui);
                     text_changed = output.response.changed();
                     self.cursor = output.cursor_range;
-                    // TODO if cursor was clicked, did we click anything interesting?
-                    if output.response.double_clicked() {
-                        // cursor will have already been moved so just use that position
-                        if let Some(cpos) = output.cursor_range {
-                            // scan the text here and see if cursor is in the middle of a hyperlink
-                            let cur = cpos.primary.ccursor.index;
-                            let c = &output.galley.text()[..cur];
-                            let front = output.galley.text()[..cur]
-                                .rfind(|ch: char| ch.is_whitespace() || ch == '\n');
-                            let back = output.galley.text()[cur..]
-                                .find(|ch: char| ch.is_whitespace() || ch == '\n');
-                            if let (Some(front), Some(back)) = (front, back) {
-                                let re = Regex::new(r".?://.?").unwrap(); // TODO cache this
-                                let selected = &output.galley.text()[front..cur + back];
-                                if re.is_match(selected) {
-                                    webbrowser::open(selected);
-                                }
-                            }
+
+                    // "(done/total)" after tasks with sub-tasks, painted so the text itself is untouched,
+                    // and kept inside the editor so a long line doesn't push it over the side panel
+                    let painter = ui.painter().sub_region(output.response.rect);
+                    let font = FontId::new(theme.font_size * 0.85, FontFamily::Proportional);
+                    for &(line_end, done, total) in self.progress_cache.get(&self.buffer) {
+                        let cursor = output.galley.from_ccursor(CCursor::new(line_end));
+                        let pos = output.text_draw_pos + output.galley.pos_from_cursor(&cursor).right_top().to_vec2();
+                        let text = format!("({}/{})", done, total);
+                        painter.text(pos + egui::vec2(8.0, 0.0), Align2::LEFT_TOP, text, font.clone(), theme.progress);
+                    }
+                    if self.scroll_to_cursor {
+                        self.scroll_to_cursor = false;
+                        if let Some(cursor) = output.cursor_range {
+                            let rect = output.galley.pos_from_cursor(&cursor.primary);
+                            ui.scroll_to_rect(rect.translate(output.text_draw_pos.to_vec2()), Some(Align::Center));
                         }
                     }
+                    if let Some(pos) = output.response.hover_pos() {
+                        let cursor = output.galley.cursor_from_pos(pos - output.text_draw_pos);
+                        // the cursor is the gap nearest the pointer, step back when the pointer is left of it
+                        let gap = output.text_draw_pos.x + output.galley.pos_from_cursor(&cursor).min.x;
+                        let index = cursor.ccursor.index;
+                        hovered = Some(if pos.x < gap { index.saturating_sub(1) } else { index });
+                        link_clicked = output.response.clicked() && ui.input().modifiers.command;
+                    }
                 });
             });
 
             if text_changed {
                 self.saved = false;
                 self.last_changed = Some(Local::now());
+                if let Some(conflict) = &mut self.conflict {
+                    conflict.diff = diff_lines(&conflict.theirs, &self.buffer);
+                }
+            }
+
+            let byte = hovered.and_then(|x| self.buffer.char_indices().nth(x)).map(|(i, _)| i);
+            if let Some(url) = byte.and_then(|x| self.highlight_cache.link_at(x)) {
+                ctx.output().cursor_icon = egui::CursorIcon::PointingHand;
+                egui::show_tooltip_text(ctx, egui::Id::new("link"), format!("{}\nCtrl+click to open", url));
+                if link_clicked {
+                    if let Err(e) = open_link(url) {
+                        self.error = Some(format!("Couldn't open {}: {}", url, e));
+                    }
+                }
             }
         });
     }
 }
 
+// the window build has no console of its own, so output would be lost without the terminal it was run from
+#[cfg(windows)]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // fails harmlessly when started from Explorer
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
 fn main() {
     let args: Vec<_> = env::args().collect();
-    let app = MyEguiApp::load(args.get(1) == Some(&"--demo".to_string()));
+    #[cfg(windows)]
+    if args.len() > 1 {
+        attach_console();
+    }
+    let options = match cli::parse(&args[1..]) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    let settings = Settings::load();
+
+    if let Some(command) = options.command {
+        let mut saved_files = SavedFiles::new(&settings);
+        if options.notes_dir.is_some() {
+            saved_files.root = options.notes_dir;
+        }
+        let git = GitBackend::from_settings(&settings, saved_files.root_dir());
+        let theme = Theme::named(&settings::themes_dir(), &settings.theme);
+        std::process::exit(cli::run(command, saved_files, git, &theme));
+    }
+
+    let app = MyEguiApp::load(options.demo, settings, options.notes_dir);
     let mut native_options = eframe::NativeOptions::default();
     native_options.maximized = true;
     eframe::run_native(Box::new(app), native_options);
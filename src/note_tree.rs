@@ -1,8 +1,9 @@
-use chrono::{Datelike};
-use eframe::egui::{self};
-use itertools::Itertools;
+use std::collections::HashMap;
 
-use crate::BufferId;
+use chrono::{Date, Datelike, Local, TimeZone};
+use eframe::egui::{self, RichText};
+
+use crate::{theme::Theme, BufferId};
 
 
 fn month_to_name(m: u32) -> &'static str {
@@ -17,30 +18,122 @@ fn month_to_name(m: u32) -> &'static str {
         8 => "Aug",
         9 => "Sep",
         10 => "Oct",
-        11 => "Now",
+        11 => "Nov",
         12 => "Dec",
         _ => "???",
     }
 }
 
-pub fn show_note_tree(buffers: &Vec::<BufferId>, ui: &mut egui::Ui) -> Option<BufferId> {
-    let mut selected = None;
-    let by_year = buffers.iter().group_by(|a|  a.date.year());
-    for (year, group) in &by_year {
-        ui.collapsing(year.to_string(), |ui| {
-            let by_month = group.group_by(|a| a.date.month());
-            for (month, group) in &by_month {
-                let month_name = month_to_name(month);
-                ui.collapsing(month_name, |ui| {
-                    for d in group.sorted_by(|a, b| Ord::cmp(&a.date, &b.date)) {
-                        let name = d.date.day().to_string();
-                        if ui.button(name).clicked() {
-                            selected = Some(d.clone());
-                        }
-                    }
-                });
-            }            
+fn first_of_month(year: i32, month: u32) -> Date<Local> {
+    Local.ymd(year, month, 1)
+}
+
+fn add_months(d: Date<Local>, months: i32) -> Date<Local> {
+    let m = d.year() * 12 + d.month0() as i32 + months;
+    first_of_month(m.div_euclid(12), m.rem_euclid(12) as u32 + 1)
+}
+
+// month calendar of the notes, starts on the month containing today
+#[derive(Debug)]
+pub struct NoteTree {
+    month: Date<Local>,
+}
+
+impl Default for NoteTree {
+    fn default() -> Self {
+        let today = Local::today();
+        Self {
+            month: first_of_month(today.year(), today.month()),
+        }
+    }
+}
+
+impl NoteTree {
+    pub fn show_month_of(&mut self, id: &BufferId) {
+        self.month = first_of_month(id.date.year(), id.date.month());
+    }
+
+    // returns the day that was clicked, which may not have a note yet
+    pub fn show(&mut self, ui: &mut egui::Ui, buffers: &[BufferId], current: &BufferId, open_tasks: &HashMap<BufferId, usize>, theme: &Theme) -> Option<BufferId> {
+        let mut selected = None;
+
+        ui.horizontal(|ui| {
+            if ui.small_button("⏪").on_hover_text("Previous year").clicked() {
+                self.month = add_months(self.month, -12);
+            }
+            if ui.small_button("⏴").on_hover_text("Previous month").clicked() {
+                self.month = add_months(self.month, -1);
+            }
+            let month = BufferId::month_of(self.month);
+            let title = RichText::new(format!("{} {}", month_to_name(self.month.month()), self.month.year()));
+            let title = if buffers.contains(&month) { title.strong() } else { title };
+            if ui.selectable_label(month == *current, title).on_hover_text("Monthly note").clicked() {
+                selected = Some(month);
+            }
+            if ui.small_button("⏵").on_hover_text("Next month").clicked() {
+                self.month = add_months(self.month, 1);
+            }
+            if ui.small_button("⏩").on_hover_text("Next year").clicked() {
+                self.month = add_months(self.month, 12);
+            }
+            if ui.small_button("Today").clicked() {
+                *self = Self::default();
+            }
         });
+
+        let today = Local::today();
+        let days_in_month = (add_months(self.month, 1) - self.month).num_days() as u32;
+        let offset = self.month.weekday().num_days_from_monday();
+
+        egui::Grid::new("calendar").num_columns(8).show(ui, |ui| {
+            ui.label("");
+            for name in ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"] {
+                ui.small(name);
+            }
+            ui.end_row();
+
+            let first_monday = self.month - chrono::Duration::days(offset as i64);
+            let show_week = |ui: &mut egui::Ui, monday: Date<Local>| {
+                let week = BufferId::week_of(monday);
+                let text = RichText::new(format!("W{}", monday.iso_week().week())).small();
+                let text = if buffers.contains(&week) { text.strong() } else { text.weak() };
+                let clicked = ui.selectable_label(week == *current, text).on_hover_text("Weekly note").clicked();
+                Some(week).filter(|_| clicked)
+            };
+
+            selected = selected.or(show_week(ui, first_monday));
+            for _ in 0..offset {
+                ui.label("");
+            }
+            for day in 1..=days_in_month {
+                if day > 1 && (offset + day - 1).is_multiple_of(7) {
+                    let monday = first_monday + chrono::Duration::days((offset + day - 1) as i64);
+                    selected = selected.or(show_week(ui, monday));
+                }
+                let id = BufferId::new(self.month.with_day(day).unwrap());
+                let mut text = RichText::new(day.to_string());
+                if let Some(n) = open_tasks.get(&id).filter(|n| **n > 0) {
+                    text = RichText::new(format!("{}·{}", day, n));
+                }
+                text = if buffers.contains(&id) { text.strong() } else { text.weak() };
+                if id.date == today {
+                    text = text.color(theme.today);
+                }
+
+                let mut response = ui.selectable_label(id == *current, text);
+                if let Some(n) = open_tasks.get(&id).filter(|n| **n > 0) {
+                    response = response.on_hover_text(format!("{} open tasks", n));
+                }
+                if response.clicked() {
+                    selected = Some(id);
+                }
+
+                if (offset + day).is_multiple_of(7) {
+                    ui.end_row();
+                }
+            }
+        });
+
+        selected
     }
-    selected
-}
\ No newline at end of file
+}
@@ -0,0 +1,128 @@
+use crate::{
+    tasks::{collect, TaskStatus},
+    BufferId, SavedFiles,
+};
+
+const SUMMARY_HEADER: &str = "# Summary";
+
+// tasks completed during the period and those still open at the end of it
+pub fn summary(saved_files: &mut SavedFiles, buffers: &[BufferId], id: &BufferId) -> String {
+    let end = id.end();
+    let before_end: Vec<_> = buffers.iter().filter(|x| x.date <= end).copied().collect();
+    let tasks = collect(saved_files, &before_end, (id, ""));
+
+    let mut out = format!("{}\n## Completed\n", SUMMARY_HEADER);
+    for task in tasks.iter().filter(|x| x.status == TaskStatus::Completed) {
+        if task.completed.is_some_and(|x| x >= id.date) {
+            out.push_str(&format!("{} {}\n", TaskStatus::Completed.marker(), task.text));
+        }
+    }
+    out.push_str("## Still open\n");
+    for task in tasks.iter().filter(|x| !x.status.is_closed()) {
+        out.push_str(&format!("{} {}\n", task.status.marker(), task.text));
+    }
+    out
+}
+
+// replaces the summary section of the note (up to the next top level header), or adds one
+pub fn with_summary(note: &str, id: &BufferId, summary: &str) -> String {
+    let mut out = String::new();
+    if note.trim().is_empty() {
+        out.push_str(&format!("# {}\n\n", id.title()));
+    }
+
+    let mut lines = note.split_inclusive('\n').peekable();
+    let mut replaced = false;
+    while let Some(line) = lines.next() {
+        if line.trim_end() == SUMMARY_HEADER && !replaced {
+            out.push_str(summary);
+            while lines.peek().is_some_and(|x| !x.starts_with("# ")) {
+                lines.next();
+            }
+            if lines.peek().is_some() {
+                out.push('\n');
+            }
+            replaced = true;
+        } else {
+            out.push_str(line);
+        }
+    }
+
+    if !replaced {
+        if !out.is_empty() && !out.ends_with("\n\n") {
+            out.push_str(if out.ends_with('\n') { "\n" } else { "\n\n" });
+        }
+        out.push_str(summary);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::notes;
+
+    // the last week of 2025 runs into 2026
+    const NOTES: [(&str, &str); 4] = [
+        ("2025-12-28", "[ ] a\n[ ] b\n[/] old\n"),
+        ("2025-12-31", "[/] a\n[ ] b\n[ ] c\n"),
+        ("2026-01-02", "[ ] b\n[ ] c\n"),
+        ("2026-01-05", "[/] b\n[ ] c\n"),
+    ];
+
+    fn summary_of(name: &str, period: &str) -> String {
+        let (_tmp, mut saved_files) = notes(name, &NOTES);
+        let buffers = saved_files.list();
+        summary(&mut saved_files, &buffers, &BufferId::parse(period).unwrap())
+    }
+
+    #[test]
+    fn week_across_the_new_year() {
+        let id = BufferId::parse("2026-W01").unwrap();
+        assert_eq!(id.date, BufferId::parse("2025-12-29").unwrap().date);
+        assert_eq!(id.title(), "Week 1, 2026");
+        assert_eq!(
+            summary_of("rollup-week", "2026-W01"),
+            "# Summary\n## Completed\n[/] a\n## Still open\n[ ] b\n[ ] c\n"
+        );
+    }
+
+    #[test]
+    fn week_before_it() {
+        assert_eq!(
+            summary_of("rollup-prev-week", "2025-W52"),
+            "# Summary\n## Completed\n[/] old\n## Still open\n[ ] a\n[ ] b\n"
+        );
+    }
+
+    #[test]
+    fn months_either_side_of_the_new_year() {
+        assert_eq!(
+            summary_of("rollup-december", "2025-12"),
+            "# Summary\n## Completed\n[/] a\n[/] old\n## Still open\n[ ] b\n[ ] c\n"
+        );
+        assert_eq!(
+            summary_of("rollup-january", "2026-01"),
+            "# Summary\n## Completed\n[/] b\n## Still open\n[ ] c\n"
+        );
+    }
+
+    #[test]
+    fn summary_is_added_to_a_new_note() {
+        let id = BufferId::parse("2026-W01").unwrap();
+        assert_eq!(with_summary("", &id, "# Summary\n"), "# Week 1, 2026\n\n# Summary\n");
+        assert_eq!(with_summary("notes\n", &id, "# Summary\n"), "notes\n\n# Summary\n");
+    }
+
+    #[test]
+    fn summary_is_replaced_in_place() {
+        let id = BufferId::parse("2026-01").unwrap();
+        let note = "# January 2026\nplans\n# Summary\n## Completed\n[/] stale\n# Later\nmore\n";
+        assert_eq!(
+            with_summary(note, &id, "# Summary\n## Completed\n[/] fresh\n"),
+            "# January 2026\nplans\n# Summary\n## Completed\n[/] fresh\n\n# Later\nmore\n"
+        );
+        let updated = with_summary(note, &id, "# Summary\n");
+        assert_eq!(with_summary(&updated, &id, "# Summary\n"), updated);
+    }
+}
@@ -0,0 +1,98 @@
+use eframe::egui::{self, Key};
+use regex::{Regex, RegexBuilder};
+
+use crate::{theme::Theme, BufferId, SavedFiles};
+
+#[derive(Debug, Clone)]
+pub struct Hit {
+    pub id: BufferId,
+    pub line_number: usize,
+    pub line: String,
+    // char range of the match within the whole note, ready to be used as a cursor
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Search {
+    query: String,
+    regex: bool,
+    hits: Vec<Hit>,
+    error: Option<String>,
+}
+
+fn find_in(id: &BufferId, text: &str, re: &Regex, hits: &mut Vec<Hit>) {
+    let mut line_start = 0;
+    for (line_number, line) in text.split('\n').enumerate() {
+        for m in re.find_iter(line).filter(|m| !m.range().is_empty()) {
+            let start = text[..line_start + m.start()].chars().count();
+            hits.push(Hit {
+                id: *id,
+                line_number: line_number + 1,
+                line: line.trim().to_owned(),
+                start,
+                end: start + m.as_str().chars().count(),
+            });
+        }
+        line_start += line.len() + 1;
+    }
+}
+
+impl Search {
+    // the open note is searched from memory as it may not have been saved yet
+    pub fn run(&mut self, saved_files: &mut SavedFiles, buffers: &[BufferId], current: (&BufferId, &str)) {
+        self.hits.clear();
+        self.error = None;
+        if self.query.is_empty() {
+            return;
+        }
+
+        let pattern = if self.regex {
+            self.query.clone()
+        } else {
+            regex::escape(&self.query)
+        };
+        let re = match RegexBuilder::new(&pattern).case_insensitive(!self.regex).build() {
+            Ok(re) => re,
+            Err(e) => {
+                self.error = Some(e.to_string());
+                return;
+            }
+        };
+
+        let hits = &mut self.hits;
+        saved_files.for_each_note(buffers, current, true, |id, text| find_in(id, text, &re, hits));
+    }
+
+    // returns the hit that was clicked
+    pub fn show(&mut self, ui: &mut egui::Ui, saved_files: &mut SavedFiles, buffers: &[BufferId], current: (&BufferId, &str), theme: &Theme) -> Option<Hit> {
+        let mut selected = None;
+        let mut run = false;
+        ui.horizontal(|ui| {
+            let response = ui.text_edit_singleline(&mut self.query);
+            run = response.lost_focus() && ui.input().key_pressed(Key::Enter);
+            run |= ui.checkbox(&mut self.regex, "Regex").changed();
+            run |= ui.button("Search").clicked();
+        });
+        if run {
+            self.run(saved_files, buffers, current);
+        }
+
+        if let Some(e) = &self.error {
+            ui.colored_label(theme.error, e);
+        } else if !self.query.is_empty() {
+            ui.label(format!("{} matches", self.hits.len()));
+        }
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for hit in &self.hits {
+                let title = format!("{} ({})", hit.id.title(), hit.line_number);
+                ui.small(title);
+                if ui.button(&hit.line).clicked() {
+                    selected = Some(hit.clone());
+                }
+            }
+        });
+        selected
+    }
+}
@@ -0,0 +1,123 @@
+use std::{fs, path::PathBuf};
+
+use directories::ProjectDirs;
+use serde_json::json;
+
+use crate::{carry_over::CarryOver, tasks::TaskStatus};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    pub notes_dir: Option<PathBuf>,
+    pub ui_scale: f32,
+    // the name of a built-in theme or a file in the themes directory
+    pub theme: String,
+    pub carry_over: CarryOver,
+    pub autosave_delay: u64,
+    pub git_enabled: bool,
+    pub git_remote: String,
+    // append the date to tasks when they're marked done
+    pub stamp_completion: bool,
+    // the states Ctrl+M steps through, in order
+    pub task_cycle: Vec<TaskStatus>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            notes_dir: None,
+            ui_scale: 1.2,
+            theme: "dark".to_owned(),
+            carry_over: Default::default(),
+            autosave_delay: 5,
+            git_enabled: false,
+            git_remote: String::new(),
+            stamp_completion: false,
+            task_cycle: vec![TaskStatus::Open, TaskStatus::Completed, TaskStatus::Cancelled],
+        }
+    }
+}
+
+pub fn config_dir() -> PathBuf {
+    if let Some(project_dirs) = ProjectDirs::from("com", "marschium", "sunrise") {
+        project_dirs.config_dir().into()
+    } else {
+        ".".into()
+    }
+}
+
+// user themes, one "<name>.json" per theme
+pub fn themes_dir() -> PathBuf {
+    let mut path = config_dir();
+    path.push("themes");
+    path
+}
+
+fn settings_path() -> PathBuf {
+    let mut path = config_dir();
+    path.push("settings.json");
+    path
+}
+
+impl Settings {
+    // missing or unreadable values fall back to the defaults
+    pub fn load() -> Self {
+        let mut s = Self::default();
+        let j = match fs::read_to_string(settings_path())
+            .ok()
+            .and_then(|text| serde_json::from_str::<serde_json::Value>(&text).ok())
+        {
+            Some(j) => j,
+            None => return s,
+        };
+
+        if let Some(notes_dir) = j["notes_dir"].as_str() {
+            s.notes_dir = Some(notes_dir.into());
+        }
+        // kept to the ranges the settings menu allows
+        if let Some(ui_scale) = j["ui_scale"].as_f64() {
+            s.ui_scale = (ui_scale as f32).clamp(0.8, 3.0);
+        }
+        if let Some(theme) = j["theme"].as_str() {
+            s.theme = theme.to_owned();
+        }
+        if let Some(carry_over) = j["carry_over"].as_str().and_then(CarryOver::from_key) {
+            s.carry_over = carry_over;
+        }
+        if let Some(autosave_delay) = j["autosave_delay"].as_u64() {
+            s.autosave_delay = autosave_delay.clamp(1, 60);
+        }
+        if let Some(git_enabled) = j["git_enabled"].as_bool() {
+            s.git_enabled = git_enabled;
+        }
+        if let Some(git_remote) = j["git_remote"].as_str() {
+            s.git_remote = git_remote.to_owned();
+        }
+        if let Some(stamp_completion) = j["stamp_completion"].as_bool() {
+            s.stamp_completion = stamp_completion;
+        }
+        if let Some(task_cycle) = j["task_cycle"].as_array() {
+            let cycle: Vec<_> = task_cycle.iter().filter_map(|x| x.as_str().and_then(TaskStatus::from_key)).collect();
+            if !cycle.is_empty() {
+                s.task_cycle = cycle;
+            }
+        }
+        s
+    }
+
+    pub fn save(&self) -> Result<(), std::io::Error> {
+        let j = json!({
+            "notes_dir": self.notes_dir.as_ref().and_then(|x| x.to_str()),
+            "ui_scale": self.ui_scale,
+            "theme": self.theme,
+            "carry_over": self.carry_over.key(),
+            "autosave_delay": self.autosave_delay,
+            "git_enabled": self.git_enabled,
+            "git_remote": self.git_remote,
+            "stamp_completion": self.stamp_completion,
+            "task_cycle": self.task_cycle.iter().map(|x| x.key()).collect::<Vec<_>>(),
+        });
+        let path = settings_path();
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, serde_json::to_string_pretty(&j)?)
+    }
+}
@@ -1,39 +1,85 @@
-use eframe::{
-    egui::{TextFormat},
-    epaint::{
-        text::{LayoutJob, LayoutSection},
-        Color32, FontFamily, FontId,
-    },
+use std::{
+    cmp::Ordering,
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    ops::Range,
 };
+
+use chrono::{Local, NaiveDate};
+use eframe::epaint::text::{LayoutJob, LayoutSection};
 use nom::{
-    bytes::complete::{tag, take_until, take_while},
-    character::{complete::{newline, not_line_ending, space0, alphanumeric1, line_ending, satisfy}, is_alphabetic},
-    sequence::{tuple, delimited, terminated},
-    IResult, branch::alt, multi::{fold_many1, many1}, combinator::not,
+    bytes::complete::{tag, take, take_until, take_while1},
+    character::complete::{digit1, newline, not_line_ending, one_of, space0, alphanumeric1, satisfy},
+    sequence::{tuple, delimited},
+    IResult, branch::alt, multi::{fold_many1, many1, many_m_n}, combinator::{map_res, not, recognize},
 };
 
-struct Style {
-    pub look: TextFormat,
+use crate::{syntax::{self, Token}, theme::Theme};
+
+// what a piece of text is, so it can be rendered somewhere other than the editor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StyleKind {
+    Text,
+    Header(usize),
+    CompletedTask,
+    CancelledTask,
+    Code,
+    Link,
+    // how the date compares to today
+    Due(Ordering),
+    Tag,
+    Priority(u8),
+    InProgressTask,
+    BlockedTask,
+    DeferredTask,
+    Bold,
+    Italic,
+    Strikethrough,
+    Quote,
+    ListMarker,
+    Rule,
+    // "[text](url)"
+    NamedLink,
+    // everything in a ``` fenced block, the fences themselves are comments
+    CodeBlock(Token),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Style {
+    pub kind: StyleKind,
     pub len: usize,
 }
 
+// letters, digits, '-' and '_'
+pub fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+// a "#tag" can't start straight after a letter or digit, so "issue#12" is plain text
+fn in_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '#'
+}
+
+// "# Header" and "##Header", but "#tag" is a tag
+pub fn is_header(line: &str) -> bool {
+    let rest = line.trim_start();
+    let title = rest.trim_start_matches('#');
+    let hashes = rest.len() - title.len();
+    hashes > 1 || (hashes == 1 && !title.starts_with(is_tag_char))
+}
+
 fn header(s: &str) -> IResult<&str, Style> {
     let mut inner = tuple((space0, many1(tag("#")), not_line_ending, newline));
     let (extra, span) = inner(s)?;
-    let num_tags = span.1.len() as f32;
-    let mut font_size = 26.0 - (4.0 * (num_tags - 1.0));
-    if font_size < 16.0 {
-        font_size = 16.0
+    let line = &s[..s.len() - extra.len()];
+    if !is_header(line) {
+        return Err(nom::Err::Error(nom::error::Error::new(s, nom::error::ErrorKind::Verify)));
     }
     Ok((
         extra,
         Style {
-            look: TextFormat {
-                font_id: FontId::new(font_size, FontFamily::Proportional),
-                color: Color32::LIGHT_GRAY,
-                ..Default::default()
-            },
-            len: span.0.len() + span.1.len() + span.2.len() + 1,
+            kind: StyleKind::Header(span.1.len()),
+            len: line.len(),
         },
     ))
 }
@@ -44,11 +90,7 @@ fn cancelled_task(s: &str) -> IResult<&str, Style> {
     Ok((
         extra,
         Style {
-            look: TextFormat {
-                font_id: FontId::new(14.0, FontFamily::Proportional),
-                color: Color32::DARK_RED,
-                ..Default::default()
-            },
+            kind: StyleKind::CancelledTask,
             len: span.0.len() + span.1.len() + span.2.len() + 1,
         },
     ))
@@ -60,72 +102,236 @@ fn completed_task(s: &str) -> IResult<&str, Style> {
     Ok((
         extra,
         Style {
-            look: TextFormat {
-                font_id: FontId::new(14.0, FontFamily::Proportional),
-                color: Color32::DARK_GREEN,
-                ..Default::default()
-            },
+            kind: StyleKind::CompletedTask,
             len: span.0.len() + span.1.len() + span.2.len() + 1,
         },
     ))
 }
 
+// only the marker is styled, the rest of the task is still live and gets its own highlighting
+fn task_marker(s: &str) -> IResult<&str, Style> {
+    let mut inner = tuple((space0, alt((tag("[~]"), tag("[!]"), tag("[>]")))));
+    let (extra, span) = inner(s)?;
+    let kind = match span.1 {
+        "[~]" => StyleKind::InProgressTask,
+        "[!]" => StyleKind::BlockedTask,
+        _ => StyleKind::DeferredTask,
+    };
+    Ok((
+        extra,
+        Style {
+            kind,
+            len: span.0.len() + span.1.len(),
+        },
+    ))
+}
+
 fn code(s: &str) -> IResult<&str, Style> {
     let mut inner = delimited(tag("`"), take_until("`"), tag("`"));
     let (extra, span) = inner(s)?;
     Ok((
         extra,
         Style {
-            look: TextFormat {
-                font_id: FontId::new(14.0, FontFamily::Monospace),
-                color: Color32::LIGHT_GRAY,
-                background: Color32::DARK_GRAY,
-                ..Default::default()
-            },
+            kind: StyleKind::Code,
             len: span.len() + 2,
         },
     ))
 }
 
+// "scheme://..." or "mailto:..."
 fn hyperlink(s: &str) -> IResult<&str, Style> {
     let allowed = |c: char| !c.is_whitespace() && c != '\n';
-    let mut hyper = tuple((alphanumeric1, tag("://"), fold_many1(satisfy(allowed), || "".to_owned(), |mut x: String, i| {x.push(i); x})));
+    let scheme = alt((recognize(tuple((alphanumeric1, tag("://")))), tag("mailto:")));
+    let mut hyper = tuple((scheme, fold_many1(satisfy(allowed), || "".to_owned(), |mut x: String, i| {x.push(i); x})));
     let (extra, span) = hyper(s)?;
     Ok((
         extra,
         Style {
-            look: TextFormat {
-                font_id: FontId::new(14.0, FontFamily::Monospace),
-                color: Color32::from_rgb(100, 168, 248),
-                ..Default::default()
-            },
+            kind: StyleKind::Link,
+            len: span.0.len() + span.1.len(),
+        },
+    ))
+}
+
+// "@2026-10-20", styled by whether it's passed, today or still to come
+fn due_date(s: &str) -> IResult<&str, Style> {
+    let mut inner = tuple((tag("@"), map_res(take(10usize), |d| NaiveDate::parse_from_str(d, "%Y-%m-%d"))));
+    let (extra, (_, date)) = inner(s)?;
+    Ok((
+        extra,
+        Style {
+            kind: StyleKind::Due(date.cmp(&Local::today().naive_local())),
+            len: 11,
+        },
+    ))
+}
+
+fn hashtag(s: &str) -> IResult<&str, Style> {
+    let mut inner = tuple((tag("#"), take_while1(is_tag_char)));
+    let (extra, span) = inner(s)?;
+    Ok((
+        extra,
+        Style {
+            kind: StyleKind::Tag,
+            len: span.0.len() + span.1.len(),
+        },
+    ))
+}
+
+// "!1" is the most urgent, "!3" the least
+fn priority(s: &str) -> IResult<&str, Style> {
+    let mut inner = tuple((tag("!"), one_of("123"), not(alphanumeric1)));
+    let (extra, (_, level, _)) = inner(s)?;
+    Ok((
+        extra,
+        Style {
+            kind: StyleKind::Priority(level as u8 - b'0'),
+            len: 2,
+        },
+    ))
+}
+
+// "*text*" like spans, the text can't be empty or start or end with a space
+fn emphasis<'a>(s: &'a str, marker: &'static str) -> IResult<&'a str, &'a str> {
+    let (extra, inner) = delimited(tag(marker), take_until(marker), tag(marker))(s)?;
+    if inner.is_empty() || inner.contains('\n') || inner.starts_with(' ') || inner.ends_with(' ') {
+        return Err(nom::Err::Error(nom::error::Error::new(s, nom::error::ErrorKind::Verify)));
+    }
+    Ok((extra, inner))
+}
+
+fn bold(s: &str) -> IResult<&str, Style> {
+    let (extra, inner) = emphasis(s, "**")?;
+    Ok((
+        extra,
+        Style {
+            kind: StyleKind::Bold,
+            len: inner.len() + 4,
+        },
+    ))
+}
+
+// "*text*" or "_text_", never started inside a word so "2*3*4" and "snake_case_name" stay plain
+fn italic(s: &str) -> IResult<&str, Style> {
+    let (extra, inner) = alt((|s| emphasis(s, "*"), |s| emphasis(s, "_")))(s)?;
+    if s.starts_with('_') && extra.starts_with(in_word) {
+        return Err(nom::Err::Error(nom::error::Error::new(s, nom::error::ErrorKind::Verify)));
+    }
+    Ok((
+        extra,
+        Style {
+            kind: StyleKind::Italic,
+            len: inner.len() + 2,
+        },
+    ))
+}
+
+fn strikethrough(s: &str) -> IResult<&str, Style> {
+    let (extra, inner) = emphasis(s, "~~")?;
+    Ok((
+        extra,
+        Style {
+            kind: StyleKind::Strikethrough,
+            len: inner.len() + 4,
+        },
+    ))
+}
+
+fn named_link(s: &str) -> IResult<&str, Style> {
+    let not_newline = |c: char| c != '\n';
+    let mut inner = tuple((
+        delimited(tag("["), take_while1(|c| c != ']' && c != '\n'), tag("]")),
+        delimited(tag("("), take_while1(|c| c != ')' && not_newline(c) && c != ' '), tag(")")),
+    ));
+    let (extra, (text, url)) = inner(s)?;
+    Ok((
+        extra,
+        Style {
+            kind: StyleKind::NamedLink,
+            len: text.len() + url.len() + 4,
+        },
+    ))
+}
+
+// "> quoted" lines
+fn quote(s: &str) -> IResult<&str, Style> {
+    let mut inner = tuple((space0, tag(">"), not_line_ending, newline));
+    let (extra, span) = inner(s)?;
+    Ok((
+        extra,
+        Style {
+            kind: StyleKind::Quote,
+            len: span.0.len() + span.1.len() + span.2.len() + 1,
+        },
+    ))
+}
+
+// only the "- ", "* " or "1. " is styled, the item itself is ordinary text
+fn list_marker(s: &str) -> IResult<&str, Style> {
+    let bullet = alt((tag("-"), tag("*"), tag("+")));
+    let number = recognize(tuple((digit1, one_of(".)"))));
+    let mut inner = tuple((space0, alt((bullet, number)), tag(" ")));
+    let (extra, span) = inner(s)?;
+    Ok((
+        extra,
+        Style {
+            kind: StyleKind::ListMarker,
             len: span.0.len() + span.1.len() + span.2.len(),
         },
     ))
 }
 
-fn style(s: &str) -> IResult<&str, Style> {
-    let (extra, style) = alt((header, completed_task, cancelled_task, code, hyperlink))(s)?;
-    Ok((extra, style))
+// "---", "***" or "___" on a line of their own, drawn struck through so it reads as a line
+fn rule(s: &str) -> IResult<&str, Style> {
+    let marks = alt((
+        recognize(many_m_n(3, usize::MAX, tuple((tag("-"), space0)))),
+        recognize(many_m_n(3, usize::MAX, tuple((tag("*"), space0)))),
+        recognize(many_m_n(3, usize::MAX, tuple((tag("_"), space0)))),
+    ));
+    let mut inner = tuple((space0, marks, newline));
+    let (extra, span) = inner(s)?;
+    Ok((
+        extra,
+        Style {
+            kind: StyleKind::Rule,
+            len: span.0.len() + span.1.len() + 1,
+        },
+    ))
+}
+
+// styles that only make sense at the start of a line
+fn line_style(s: &str) -> IResult<&str, Style> {
+    alt((header, completed_task, cancelled_task, task_marker, rule, quote, list_marker))(s)
+}
+
+fn inline_style(s: &str) -> IResult<&str, Style> {
+    alt((code, hyperlink, named_link, bold, strikethrough, italic, due_date, hashtag, priority))(s)
+}
+
+// inside a word only the styles that can't be mistaken for part of it, so no tags or italics
+fn word_style(s: &str) -> IResult<&str, Style> {
+    alt((code, hyperlink, named_link, bold, strikethrough, due_date, priority))(s)
 }
 
-fn parse(input: &str) -> IResult<&str, Vec<Style>> {
+pub fn parse(input: &str) -> IResult<&str, Vec<Style>> {
     let mut output = Vec::new();
     let mut current_input = input;
 
     while !current_input.is_empty() {
         let mut at_least_one_style = false;
         for (idx, _) in current_input.char_indices() {
-            match style(&current_input[idx..]) {
+            let offset = input.len() - current_input.len() + idx;
+            let result = match input[..offset].chars().next_back() {
+                None | Some('\n') => alt((line_style, inline_style))(&current_input[idx..]),
+                Some(c) if in_word(c) => word_style(&current_input[idx..]),
+                Some(_) => inline_style(&current_input[idx..]),
+            };
+            match result {
                 Ok((remaining, style)) => {
                     let text_until_style = &current_input[0..idx];
                     if !text_until_style.is_empty() {
                         output.push(Style {
-                            look: TextFormat {
-                                font_id: FontId::new(14.0, FontFamily::Proportional),
-                                color: Color32::LIGHT_GRAY,
-                                ..Default::default()
-                            },
+                            kind: StyleKind::Text,
                             len: text_until_style.len(),
                         });
                     }
@@ -141,11 +347,7 @@ fn parse(input: &str) -> IResult<&str, Vec<Style>> {
 
         if !at_least_one_style {
             output.push(Style {
-                look: TextFormat {
-                    font_id: FontId::new(14.0, FontFamily::Proportional),
-                    color: Color32::LIGHT_GRAY,
-                    ..Default::default()
-                },
+                kind: StyleKind::Text,
                 len: current_input.len(),
             });
             break;
@@ -155,42 +357,292 @@ fn parse(input: &str) -> IResult<&str, Vec<Style>> {
     Ok(("", output))
 }
 
-pub fn highlight(text: &str) -> LayoutJob {
-    let mut job = LayoutJob::default();
-    job.text = text.into();
-
-    match parse(text) {
-        Ok((_, styles)) => {
-            let mut offset = 0;
-            for style in styles {
-                job.sections.push(LayoutSection {
-                    byte_range: offset..offset + style.len,
-                    leading_space: 0.0,
-                    format: style.look,
-                });
-                offset += style.len;
+// styles for a single line, the line based parsers expect it to end in a newline
+pub fn parse_line(line: &str) -> Vec<Style> {
+    let mut styles = if line.ends_with('\n') {
+        parse(line).map(|(_, x)| x).unwrap_or_default()
+    } else {
+        let mut styles = parse(&format!("{}\n", line)).map(|(_, x)| x).unwrap_or_default();
+        // give back the newline that was added
+        if let Some(last) = styles.last_mut() {
+            last.len -= 1;
+        }
+        styles
+    };
+    styles.retain(|x| x.len > 0);
+    styles
+}
+
+// where a Link or NamedLink span points to
+pub fn link_target(kind: StyleKind, span: &str) -> Option<&str> {
+    match kind {
+        StyleKind::Link => Some(span),
+        StyleKind::NamedLink => span[1..span.len() - 1].split_once("](").map(|(_, url)| url),
+        _ => None,
+    }
+}
+
+// the schemes a Ctrl+click opens, anything else could start a program
+const OPENABLE: [&str; 4] = ["http", "https", "file", "mailto"];
+
+// the lowercase scheme of a link that's safe to open
+pub fn openable_scheme(url: &str) -> Option<String> {
+    let (scheme, _) = url.split_once(':')?;
+    let scheme = scheme.to_ascii_lowercase();
+    OPENABLE.contains(&scheme.as_str()).then_some(scheme)
+}
+
+// byte ranges of the "#tag"s in a line, including the '#', found by the same rule the highlighter uses
+pub fn tags_in(line: &str) -> Vec<Range<usize>> {
+    let mut tags = Vec::new();
+    let mut prev = None;
+    for (i, c) in line.char_indices() {
+        if c == '#' && !prev.is_some_and(in_word) {
+            if let Ok((_, style)) = hashtag(&line[i..]) {
+                tags.push(i..i + style.len);
             }
         }
-        _ => {}
+        prev = Some(c);
+    }
+    tags
+}
+
+// a line inside a fenced block, coloured by language when it's one we know
+fn code_line(line: &str, lang: &str) -> Vec<Style> {
+    let tokens = match syntax::language(lang) {
+        Some(lang) => syntax::tokens(line, lang),
+        None => vec![(line.len(), Token::Plain)],
+    };
+    tokens
+        .into_iter()
+        .filter(|(len, _)| *len > 0)
+        .map(|(len, token)| Style { kind: StyleKind::CodeBlock(token), len })
+        .collect()
+}
+
+// "```" or "```lang", a line like "```code```" is inline code rather than a fence
+fn is_fence(line: &str) -> bool {
+    match line.trim().strip_prefix("```") {
+        Some(lang) => !lang.trim_start().contains(|c: char| c == '`' || c.is_whitespace()),
+        None => false,
+    }
+}
+
+// 'fence' is the language of the ``` block the line is in, "" for none given
+fn style_line(line: &str, fence: Option<&str>) -> Vec<Style> {
+    if is_fence(line) {
+        return vec![Style { kind: StyleKind::CodeBlock(Token::Comment), len: line.len() }];
+    }
+    match fence {
+        Some(lang) => code_line(line, lang),
+        None => parse_line(line),
+    }
+}
+
+// a ``` line opens a block, with the language after it, or closes the open one
+pub fn next_fence(line: &str, fence: &mut Option<String>) {
+    if is_fence(line) {
+        *fence = match fence {
+            Some(_) => None,
+            None => Some(line.trim().trim_start_matches('`').trim().to_owned()),
+        };
+    }
+}
+
+// the styles of a whole note, parsed a line at a time
+pub fn styles(text: &str) -> Vec<Style> {
+    let mut fence = None;
+    let mut out = Vec::new();
+    for line in text.split_inclusive('\n') {
+        out.extend(style_line(line, fence.as_deref()));
+        next_fence(line, &mut fence);
     }
+    out
+}
 
-    job
+fn push_sections(job: &mut LayoutJob, offset: usize, styles: &[Style], theme: &Theme) {
+    let mut offset = offset;
+    for style in styles {
+        job.sections.push(LayoutSection {
+            byte_range: offset..offset + style.len,
+            leading_space: 0.0,
+            format: theme.format(style.kind),
+        });
+        offset += style.len;
+    }
 }
 
+fn hash(line: &str, fence: Option<&str>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    line.hash(&mut hasher);
+    fence.hash(&mut hasher);
+    hasher.finish()
+}
+
+// highlighting is cached per line by the hash of the line and the code block it's in, so only lines
+// that changed get parsed again, the theme is applied afterwards so changing it doesn't need a re-parse
 #[derive(Default, Debug)]
 pub struct CachedLayoutJobBuilder {
-    cached: Option<LayoutJob>
+    lines: HashMap<u64, Vec<Style>>,
+    // due dates look different depending on the day
+    day: Option<NaiveDate>,
+    // byte ranges of the links in the last highlighted text and where they point
+    links: Vec<(Range<usize>, String)>,
 }
 
 impl CachedLayoutJobBuilder {
-    pub fn clear(&mut self) {
-        self.cached = None
+    pub fn highlight(&mut self, text: &str, theme: &Theme) -> LayoutJob {
+        let today = Local::today().naive_local();
+        if self.day != Some(today) {
+            self.lines.clear();
+            self.day = Some(today);
+        }
+
+        let mut job = LayoutJob { text: text.into(), ..Default::default() };
+        let mut lines = HashMap::with_capacity(self.lines.len());
+        self.links.clear();
+        let mut offset = 0;
+        let mut fence = None;
+        for line in text.split_inclusive('\n') {
+            let key = hash(line, fence.as_deref());
+            let styles = match self.lines.remove(&key).or_else(|| lines.remove(&key)) {
+                Some(styles) => styles,
+                None => style_line(line, fence.as_deref()),
+            };
+            push_sections(&mut job, offset, &styles, theme);
+            let mut start = offset;
+            for style in &styles {
+                let span = &text[start..start + style.len];
+                if let Some(url) = link_target(style.kind, span) {
+                    self.links.push((start..start + style.len, url.to_owned()));
+                }
+                start += style.len;
+            }
+            lines.insert(key, styles);
+            next_fence(line, &mut fence);
+            offset += line.len();
+        }
+        // lines that are no longer in the note are dropped
+        self.lines = lines;
+        job
+    }
+
+    // the link covering the byte at 'index' of the last highlighted text
+    pub fn link_at(&self, index: usize) -> Option<&str> {
+        self.links.iter().find(|(range, _)| range.contains(&index)).map(|(_, url)| url.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // each style with the text it covers
+    fn spans(line: &str) -> Vec<(StyleKind, &str)> {
+        let mut start = 0;
+        let mut spans = Vec::new();
+        for style in parse_line(line) {
+            spans.push((style.kind, &line[start..start + style.len]));
+            start += style.len;
+        }
+        spans
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(
+            spans("[ ] ship `v2` !1"),
+            [(StyleKind::Text, "[ ] ship "), (StyleKind::Code, "`v2`"), (StyleKind::Text, " "), (StyleKind::Priority(1), "!1")]
+        );
+        assert_eq!(spans("[/] done"), [(StyleKind::CompletedTask, "[/] done")]);
+        assert_eq!(spans("[x] dropped\n"), [(StyleKind::CancelledTask, "[x] dropped\n")]);
+        assert_eq!(spans("see https://example.com"), [(StyleKind::Text, "see "), (StyleKind::Link, "https://example.com")]);
+        assert_eq!(spans(""), []);
+        // an unclosed ` is plain text, styles don't run on to the next line
+        assert_eq!(spans("a `b"), [(StyleKind::Text, "a `b")]);
+    }
+
+    #[test]
+    fn whole_notes_match_line_by_line() {
+        let text = "# Day\n[ ] a #work\n```\nlet x = 1;\n```\nplain";
+        let lens: usize = styles(text).iter().map(|x| x.len).sum();
+        assert_eq!(lens, text.len());
+        let headers = style_line("# Day\n", None);
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0].kind, StyleKind::Header(1));
+        // inside a fence nothing is parsed as Markdown
+        let code = style_line("# not a header\n", Some(""));
+        assert!(code.iter().all(|x| x.kind == StyleKind::CodeBlock(Token::Plain)));
+    }
+
+    #[test]
+    fn italics_start_a_word() {
+        assert_eq!(spans("an *easy* _one_"), [(StyleKind::Text, "an "), (StyleKind::Italic, "*easy*"), (StyleKind::Text, " "), (StyleKind::Italic, "_one_")]);
+        assert_eq!(spans("(*aside*)")[1], (StyleKind::Italic, "*aside*"));
+        for line in ["2*3*4", "file*name*", "snake_case_name", "_snake_case", "a * b * c"] {
+            assert!(spans(line).iter().all(|x| x.0 == StyleKind::Text), "{}", line);
+        }
+    }
+
+    #[test]
+    fn fences() {
+        let mut fence = None;
+        next_fence("```sql\n", &mut fence);
+        assert_eq!(fence.as_deref(), Some("sql"));
+        next_fence("select 1\n", &mut fence);
+        assert_eq!(fence.as_deref(), Some("sql"));
+        next_fence("  ```\n", &mut fence);
+        assert_eq!(fence, None);
+        next_fence("``` rust", &mut fence);
+        assert_eq!(fence.as_deref(), Some("rust"));
+        next_fence("```", &mut fence);
+        for line in ["```inline```", "```let x = 1;```\n", "``` two words", "text ```"] {
+            next_fence(line, &mut fence);
+            assert_eq!(fence, None, "{}", line);
+        }
+        assert!(spans("```x```").iter().all(|x| x.0 == StyleKind::Code));
+    }
+
+    #[test]
+    fn links() {
+        let named = "[the docs](https://example.com/a?b=c)";
+        assert_eq!(link_target(StyleKind::NamedLink, named), Some("https://example.com/a?b=c"));
+        assert_eq!(link_target(StyleKind::Link, "mailto:me@example.com"), Some("mailto:me@example.com"));
+        assert_eq!(link_target(StyleKind::Text, "https://example.com"), None);
+        assert_eq!(spans(&format!("see {}", named))[1], (StyleKind::NamedLink, named));
+
+        assert_eq!(openable_scheme("HTTPS://example.com").as_deref(), Some("https"));
+        assert_eq!(openable_scheme("file:///tmp/notes.txt").as_deref(), Some("file"));
+        for url in ["javascript:alert(1)", "ms-settings:", "smb://server/share", "notes/today.md", "calc.exe"] {
+            assert_eq!(openable_scheme(url), None, "{}", url);
+        }
     }
 
-    pub fn highlight(&mut self, text: &str) -> LayoutJob {
-        if self.cached.is_none() {             
-            self.cached = Some(highlight(text));
+    #[test]
+    fn headers_and_tags() {
+        assert!(is_header("# Work"));
+        assert!(is_header("  ##Work"));
+        assert!(is_header("#"));
+        assert!(!is_header("#work"));
+        assert!(!is_header("plain"));
+        assert_eq!(spans("# Work"), [(StyleKind::Header(1), "# Work")]);
+        assert_eq!(spans("##Work"), [(StyleKind::Header(2), "##Work")]);
+        assert_eq!(spans("#work today"), [(StyleKind::Tag, "#work"), (StyleKind::Text, " today")]);
+    }
+
+    #[test]
+    fn tags_need_a_word_boundary() {
+        assert_eq!(spans("issue#12 and #big-one"), [(StyleKind::Text, "issue#12 and "), (StyleKind::Tag, "#big-one")]);
+        assert_eq!(tags_in("issue#12 and #big-one #x"), [13..21, 22..24]);
+        assert_eq!(tags_in("#a ##b (#c_d) é#e"), [0..2, 8..12]);
+    }
+
+    #[test]
+    fn highlighter_and_tags_agree() {
+        for line in ["#a, #b.#c", "x#y #z", "- [ ] #todo !1", "#1 #ünï"] {
+            let tags: Vec<_> = spans(line).into_iter().filter(|x| x.0 == StyleKind::Tag).map(|x| x.1).collect();
+            let found: Vec<_> = tags_in(line).into_iter().map(|x| &line[x]).collect();
+            assert_eq!(tags, found, "{}", line);
         }
-        self.cached.to_owned().unwrap()
     }
-}
\ No newline at end of file
+}
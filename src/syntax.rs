@@ -0,0 +1,180 @@
+// a small tokenizer for the languages that get pasted into notes, good enough to colour a line
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    Plain,
+    Keyword,
+    String,
+    Number,
+    Comment,
+    Variable,
+}
+
+pub struct Language {
+    keywords: &'static [&'static str],
+    case_sensitive: bool,
+    comment: Option<&'static str>,
+    quotes: &'static [char],
+    // "$HOME" style variables
+    variables: bool,
+}
+
+const RUST: Language = Language {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "false", "fn", "for", "if", "impl",
+        "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super",
+        "trait", "true", "type", "unsafe", "use", "where", "while", "Some", "None", "Ok", "Err",
+    ],
+    case_sensitive: true,
+    comment: Some("//"),
+    quotes: &['"'],
+    variables: false,
+};
+
+const SHELL: Language = Language {
+    keywords: &[
+        "if", "then", "else", "elif", "fi", "for", "while", "until", "do", "done", "case", "esac", "in", "function", "return",
+        "export", "local", "readonly", "source", "echo", "cd", "sudo", "exit",
+    ],
+    case_sensitive: true,
+    comment: Some("#"),
+    quotes: &['"', '\''],
+    variables: true,
+};
+
+const SQL: Language = Language {
+    keywords: &[
+        "select", "from", "where", "and", "or", "not", "null", "is", "in", "like", "between", "insert", "into", "values",
+        "update", "set", "delete", "create", "alter", "drop", "table", "index", "view", "join", "inner", "left", "right",
+        "outer", "on", "group", "by", "order", "having", "limit", "offset", "as", "distinct", "union", "all", "case", "when",
+        "then", "else", "end", "primary", "key", "foreign", "references", "default", "asc", "desc", "with", "returning",
+    ],
+    case_sensitive: false,
+    comment: Some("--"),
+    quotes: &['\'', '"'],
+    variables: false,
+};
+
+const JSON: Language = Language {
+    keywords: &["true", "false", "null"],
+    case_sensitive: true,
+    comment: None,
+    quotes: &['"'],
+    variables: false,
+};
+
+// the name after the opening ``` of a fenced block
+pub fn language(name: &str) -> Option<&'static Language> {
+    match name.trim().to_lowercase().as_str() {
+        "rust" | "rs" => Some(&RUST),
+        "sh" | "bash" | "shell" | "zsh" | "console" => Some(&SHELL),
+        "sql" => Some(&SQL),
+        "json" => Some(&JSON),
+        _ => None,
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// (byte length, token) pairs covering the whole line
+pub fn tokens(line: &str, lang: &Language) -> Vec<(usize, Token)> {
+    let mut out: Vec<(usize, Token)> = Vec::new();
+    let mut push = |len: usize, token: Token| match out.last_mut() {
+        Some((last, Token::Plain)) if token == Token::Plain => *last += len,
+        _ => out.push((len, token)),
+    };
+
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        let len = if lang.comment.is_some_and(|x| rest.starts_with(x)) {
+            let len = rest.trim_end_matches('\n').len();
+            push(len, Token::Comment);
+            len
+        } else if lang.quotes.contains(&c) {
+            // up to the closing quote, skipping escaped ones, or the end of the line
+            let mut escaped = false;
+            let end = rest[1..]
+                .char_indices()
+                .find(|(_, x)| {
+                    let closes = *x == c && !escaped;
+                    escaped = *x == '\\' && !escaped;
+                    closes || *x == '\n'
+                })
+                .map_or(rest.len(), |(i, x)| if x == '\n' { i + 1 } else { i + 2 });
+            push(end, Token::String);
+            end
+        } else if lang.variables && c == '$' {
+            let len = 1 + rest[1..].find(|x: char| !is_word(x) && x != '{' && x != '}').unwrap_or(rest.len() - 1);
+            push(len, Token::Variable);
+            len
+        } else if c.is_ascii_digit() {
+            let len = rest.find(|x: char| !x.is_ascii_alphanumeric() && x != '.' && x != '_').unwrap_or(rest.len());
+            push(len, Token::Number);
+            len
+        } else if is_word(c) {
+            let len = rest.find(|x: char| !is_word(x)).unwrap_or(rest.len());
+            let word = &rest[..len];
+            let keyword = if lang.case_sensitive {
+                lang.keywords.contains(&word)
+            } else {
+                lang.keywords.iter().any(|x| x.eq_ignore_ascii_case(word))
+            };
+            push(len, if keyword { Token::Keyword } else { Token::Plain });
+            len
+        } else {
+            push(c.len_utf8(), Token::Plain);
+            c.len_utf8()
+        };
+        rest = &rest[len..];
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // each token with the text it covers
+    fn spans<'a>(line: &'a str, lang: &str) -> Vec<(&'a str, Token)> {
+        let mut start = 0;
+        let mut spans = Vec::new();
+        for (len, token) in tokens(line, language(lang).unwrap()) {
+            spans.push((&line[start..start + len], token));
+            start += len;
+        }
+        spans
+    }
+
+    #[test]
+    fn rust() {
+        assert_eq!(
+            spans("let x = \"a \\\" b\"; // 42", "rust"),
+            [
+                ("let", Token::Keyword),
+                (" x = ", Token::Plain),
+                ("\"a \\\" b\"", Token::String),
+                ("; ", Token::Plain),
+                ("// 42", Token::Comment),
+            ]
+        );
+        assert_eq!(spans("0x1f_u8", "rs"), [("0x1f_u8", Token::Number)]);
+    }
+
+    #[test]
+    fn shell_and_sql() {
+        assert_eq!(
+            spans("echo $HOME # home", "bash"),
+            [("echo", Token::Keyword), (" ", Token::Plain), ("$HOME", Token::Variable), (" ", Token::Plain), ("# home", Token::Comment)]
+        );
+        assert_eq!(spans("SELECT id", "SQL"), [("SELECT", Token::Keyword), (" id", Token::Plain)]);
+    }
+
+    #[test]
+    fn unclosed_strings_end_with_the_line() {
+        assert_eq!(spans("\"open\n", "json"), [("\"open", Token::String), ("\n", Token::Plain)]);
+        assert!(language("cobol").is_none());
+        assert_eq!(tokens("", &JSON), []);
+    }
+}
@@ -0,0 +1,101 @@
+use std::{collections::HashMap, ops::Range};
+
+use eframe::egui;
+
+use crate::{search::Hit, style::tags_in, tasks::TaskStatus, BufferId, SavedFiles};
+
+// state of the Tags panel, recomputed lazily after notes change
+#[derive(Debug, Default)]
+pub struct TagFilter {
+    selected: Option<String>,
+    tasks_only: bool,
+    // every tag with the number of lines it's on, most used first
+    tags: Option<Vec<(String, usize)>>,
+    hits: Option<Vec<Hit>>,
+}
+
+impl TagFilter {
+    pub fn invalidate(&mut self) {
+        self.tags = None;
+        self.hits = None;
+    }
+
+    fn collect_tags(saved_files: &mut SavedFiles, buffers: &[BufferId], current: (&BufferId, &str)) -> Vec<(String, usize)> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        saved_files.for_each_note(buffers, current, true, |_, text| {
+            for line in text.lines() {
+                let mut seen: Vec<&str> = tags_in(line).into_iter().map(|x| &line[x.start + 1..x.end]).collect();
+                seen.dedup();
+                for tag in seen {
+                    *counts.entry(tag.to_lowercase()).or_default() += 1;
+                }
+            }
+        });
+        let mut tags: Vec<_> = counts.into_iter().collect();
+        tags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        tags
+    }
+
+    fn find(&self, saved_files: &mut SavedFiles, buffers: &[BufferId], current: (&BufferId, &str)) -> Vec<Hit> {
+        let selected = match &self.selected {
+            Some(selected) => selected,
+            None => return Vec::new(),
+        };
+        let mut hits = Vec::new();
+        saved_files.for_each_note(buffers, current, true, |id, text| {
+            let mut line_start = 0;
+            for (line_number, line) in text.split('\n').enumerate() {
+                let is_task = TaskStatus::parse_line(line).is_some();
+                let found = tags_in(line).into_iter().find(|x| line[x.start + 1..x.end].to_lowercase() == *selected);
+                if let Some(Range { start: s, end: e }) = found.filter(|_| is_task || !self.tasks_only) {
+                    let start = text[..line_start + s].chars().count();
+                    hits.push(Hit {
+                        id: *id,
+                        line_number: line_number + 1,
+                        line: line.trim().to_owned(),
+                        start,
+                        end: start + line[s..e].chars().count(),
+                    });
+                }
+                line_start += line.len() + 1;
+            }
+        });
+        hits
+    }
+
+    // returns the line that was clicked
+    pub fn show(&mut self, ui: &mut egui::Ui, saved_files: &mut SavedFiles, buffers: &[BufferId], current: (&BufferId, &str)) -> Option<Hit> {
+        let mut clicked = None;
+        if self.tags.is_none() {
+            self.tags = Some(Self::collect_tags(saved_files, buffers, current));
+        }
+
+        let before = (self.selected.clone(), self.tasks_only);
+        ui.horizontal_wrapped(|ui| {
+            for (tag, count) in self.tags.as_deref().unwrap_or_default() {
+                let is_selected = self.selected.as_ref() == Some(tag);
+                let response = ui.selectable_label(is_selected, format!("#{}", tag));
+                if response.on_hover_text(format!("{} lines", count)).clicked() {
+                    self.selected = if is_selected { None } else { Some(tag.clone()) };
+                }
+            }
+        });
+        ui.checkbox(&mut self.tasks_only, "Tasks only");
+        if (self.selected.clone(), self.tasks_only) != before {
+            self.hits = None;
+        }
+
+        if self.hits.is_none() {
+            self.hits = Some(self.find(saved_files, buffers, current));
+        }
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for hit in self.hits.as_deref().unwrap_or_default() {
+                ui.small(format!("{} ({})", hit.id.title(), hit.line_number));
+                if ui.button(&hit.line).clicked() {
+                    clicked = Some(hit.clone());
+                }
+            }
+        });
+        clicked
+    }
+}
@@ -0,0 +1,620 @@
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+};
+
+use chrono::{Date, Local, NaiveDate, TimeZone};
+use eframe::egui;
+
+use crate::{theme::Theme, BufferId, SavedFiles};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TaskStatus {
+    Open,
+    InProgress,
+    Blocked,
+    Deferred,
+    Completed,
+    Cancelled,
+}
+
+impl TaskStatus {
+    pub const ALL: [TaskStatus; 6] = [
+        TaskStatus::Open,
+        TaskStatus::InProgress,
+        TaskStatus::Blocked,
+        TaskStatus::Deferred,
+        TaskStatus::Completed,
+        TaskStatus::Cancelled,
+    ];
+
+    pub fn marker(&self) -> &'static str {
+        match self {
+            TaskStatus::Open => "[ ]",
+            TaskStatus::InProgress => "[~]",
+            TaskStatus::Blocked => "[!]",
+            TaskStatus::Deferred => "[>]",
+            TaskStatus::Completed => "[/]",
+            TaskStatus::Cancelled => "[x]",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TaskStatus::Open => "Open",
+            TaskStatus::InProgress => "In progress",
+            TaskStatus::Blocked => "Blocked",
+            TaskStatus::Deferred => "Deferred",
+            TaskStatus::Completed => "Completed",
+            TaskStatus::Cancelled => "Cancelled",
+        }
+    }
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            TaskStatus::Open => "open",
+            TaskStatus::InProgress => "in_progress",
+            TaskStatus::Blocked => "blocked",
+            TaskStatus::Deferred => "deferred",
+            TaskStatus::Completed => "completed",
+            TaskStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|x| x.key() == key)
+    }
+
+    // completed and cancelled tasks are done with, every other state still needs doing
+    pub fn is_closed(&self) -> bool {
+        matches!(self, TaskStatus::Completed | TaskStatus::Cancelled)
+    }
+
+    // the state after this one in 'cycle', states outside the cycle go to its start, from the end
+    // it goes back to the first other closed state so a closed task isn't reopened, e.g. [x] → [/]
+    pub fn next_in(&self, cycle: &[TaskStatus]) -> TaskStatus {
+        match cycle.iter().position(|x| x == self) {
+            Some(i) if i + 1 < cycle.len() => cycle[i + 1],
+            Some(_) => cycle.iter().copied().find(|x| x.is_closed() && x != self).unwrap_or(cycle[0]),
+            None => cycle.first().copied().unwrap_or(TaskStatus::Open),
+        }
+    }
+
+    // splits a task line into its status and text, also when it's written as a Markdown list item
+    // like "- [ ] foo", "[]" is accepted as an open task
+    pub fn parse_line(line: &str) -> Option<(TaskStatus, &str)> {
+        let line = &line[list_prefix(line)..];
+        let (status, rest) = if let Some(rest) = line.strip_prefix("[]") {
+            (TaskStatus::Open, rest)
+        } else {
+            let status = Self::ALL.into_iter().find(|x| line.starts_with(x.marker()))?;
+            (status, &line[status.marker().len()..])
+        };
+        Some((status, rest.trim()))
+    }
+}
+
+// completed tasks can carry the day they were done, e.g. "[/] fix bug ✓2026-10-17"
+const STAMP: &str = " ✓";
+
+// splits the completion date off the end of a task's text
+pub fn split_stamp(text: &str) -> (&str, Option<NaiveDate>) {
+    if let Some((rest, date)) = text.rsplit_once(STAMP) {
+        if let Ok(date) = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d") {
+            return (rest.trim_end(), Some(date));
+        }
+    }
+    (text, None)
+}
+
+// drops any completion date from a task line and adds 'today' if the task is completed
+pub fn restamp(line: &str, today: Date<Local>) -> String {
+    let (unstamped, _) = split_stamp(line);
+    match TaskStatus::parse_line(unstamped) {
+        Some((TaskStatus::Completed, _)) => format!("{}{}{}", unstamped, STAMP, today.format("%Y-%m-%d")),
+        _ => unstamped.to_owned(),
+    }
+}
+
+// the first "@YYYY-MM-DD" in a task's text
+pub fn due_date(text: &str) -> Option<NaiveDate> {
+    text.split_whitespace()
+        .filter_map(|x| x.strip_prefix('@'))
+        .find_map(|x| NaiveDate::parse_from_str(x, "%Y-%m-%d").ok())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Task {
+    pub text: String,
+    // status as of the most recent note the task appears in
+    pub status: TaskStatus,
+    pub first_seen: BufferId,
+    pub last_seen: BufferId,
+    // from the completion date on the task if it has one, otherwise the first note it was closed in
+    pub completed: Option<Date<Local>>,
+    pub due: Option<Date<Local>>,
+}
+
+impl Task {
+    // days between first being written down and being closed (or today if still open)
+    pub fn age(&self) -> i64 {
+        let end = self.completed.unwrap_or_else(Local::today);
+        (end - self.first_seen.date).num_days()
+    }
+}
+
+// tasks are matched between days by their text, weekly and monthly notes are left out
+pub fn collect(saved_files: &mut SavedFiles, buffers: &[BufferId], current: (&BufferId, &str)) -> Vec<Task> {
+    let days: Vec<_> = buffers.iter().filter(|x| x.is_day()).copied().collect();
+    let mut tasks: Vec<Task> = Vec::new();
+    let mut by_text: HashMap<String, usize> = HashMap::new();
+    saved_files.for_each_note(&days, current, false, |id, text| {
+        if !id.is_day() {
+            return;
+        }
+        for (status, text) in text.lines().filter_map(TaskStatus::parse_line) {
+            let (text, stamp) = split_stamp(text);
+            if text.is_empty() {
+                continue;
+            }
+            let i = *by_text.entry(text.to_owned()).or_insert_with(|| {
+                tasks.push(Task {
+                    text: text.to_owned(),
+                    status,
+                    first_seen: *id,
+                    last_seen: *id,
+                    completed: None,
+                    due: due_date(text).and_then(|x| Local.from_local_date(&x).single()),
+                });
+                tasks.len() - 1
+            });
+            let task = &mut tasks[i];
+            task.status = status;
+            task.last_seen = *id;
+            let stamp = stamp.and_then(|x| Local.from_local_date(&x).single());
+            match status {
+                _ if !status.is_closed() => task.completed = None,
+                _ if task.completed.is_none() => task.completed = Some(stamp.unwrap_or(id.date)),
+                _ => {}
+            }
+        }
+    });
+    tasks
+}
+
+// adds open tasks due on 'day' to its note unless they're already in it, returns true if any were added,
+// overdue ones are left to the Upcoming panel so a task removed from a note doesn't keep coming back
+pub fn insert_due(saved_files: &mut SavedFiles, day: &BufferId, buf: &mut String) -> bool {
+    let buffers = saved_files.list();
+    let due: Vec<_> = collect(saved_files, &buffers, (day, buf))
+        .into_iter()
+        .filter(|x| !x.status.is_closed() && x.last_seen != *day)
+        .filter(|x| x.due == Some(day.date))
+        .collect();
+    if due.is_empty() {
+        return false;
+    }
+
+    if !buf.is_empty() && !buf.ends_with('\n') {
+        buf.push('\n');
+    }
+    for task in due {
+        buf.push_str(&format!("{} {}\n", task.status.marker(), task.text));
+    }
+    true
+}
+
+// number of open tasks written in each note
+pub fn open_counts(saved_files: &mut SavedFiles, buffers: &[BufferId], current: (&BufferId, &str)) -> HashMap<BufferId, usize> {
+    let count = |text: &str| {
+        text.lines()
+            .filter(|l| TaskStatus::parse_line(l).is_some_and(|(status, _)| !status.is_closed()))
+            .count()
+    };
+
+    let mut counts = HashMap::new();
+    let mut buf = String::new();
+    for id in buffers.iter().filter(|x| x.is_day() && *x != current.0) {
+        if saved_files.load(id, &mut buf).is_ok() {
+            counts.insert(*id, count(&buf));
+        }
+    }
+    counts.insert(*current.0, count(current.1));
+    counts
+}
+
+// length of the indent and any "- ", "* ", "+ " or "1. " list marker before a line's text
+pub fn list_prefix(line: &str) -> usize {
+    let rest = line.trim_start();
+    let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let marker = if ["- ", "* ", "+ "].iter().any(|x| rest.starts_with(x)) {
+        1
+    } else if digits > 0 && rest[digits..].starts_with(". ") {
+        digits + 1
+    } else {
+        return line.len() - rest.len();
+    };
+    line.len() - rest[marker..].trim_start().len()
+}
+
+// gives a line the status 'to', lines that aren't tasks become one
+pub fn set_line_status(line: &str, to: TaskStatus) -> String {
+    let prefix = list_prefix(line);
+    let rest = &line[prefix..];
+    match TaskStatus::parse_line(rest) {
+        Some(_) if rest.starts_with('[') => {
+            let marker_len = if rest.starts_with("[]") { 2 } else { 3 };
+            format!("{}{}{}", &line[..prefix], to.marker(), &rest[marker_len..])
+        }
+        _ => format!("{}{} {}", &line[..prefix], to.marker(), rest),
+    }
+}
+
+// what Ctrl+M does, lines that aren't tasks become open ones and tasks move along 'cycle'
+pub fn cycle_line(line: &str, cycle: &[TaskStatus]) -> String {
+    match TaskStatus::parse_line(line) {
+        // "[]" is tidied up to "[ ]" first
+        Some(_) if line[list_prefix(line)..].starts_with("[]") => set_line_status(line, TaskStatus::Open),
+        Some((status, _)) => set_line_status(line, status.next_in(cycle)),
+        None => set_line_status(line, TaskStatus::Open),
+    }
+}
+
+// change the status of the first task line matching 'task', returns false if there wasn't one
+pub fn set_status(buf: &mut String, task: &str, from: TaskStatus, to: TaskStatus, stamp: bool) -> bool {
+    let mut offset = 0;
+    for (i, line) in buf.split_inclusive('\n').enumerate() {
+        let found = TaskStatus::parse_line(line).map(|(status, text)| (status, split_stamp(text).0));
+        if found == Some((from, task)) {
+            let line = line.trim_end_matches('\n');
+            let mut changed = set_line_status(line, to);
+            if stamp && from != to {
+                changed = restamp(&changed, Local::today());
+            }
+            buf.replace_range(offset..offset + line.len(), &changed);
+            close_subtasks(buf, i, stamp);
+            return true;
+        }
+        offset += line.len();
+    }
+    false
+}
+
+// width of a line's leading whitespace, a tab counts as four spaces
+fn indent_width(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+// for each line, the task it's nested under by indentation, blank lines don't break the nesting
+pub fn parents(lines: &[&str]) -> Vec<Option<usize>> {
+    let mut out = Vec::with_capacity(lines.len());
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            out.push(None);
+            continue;
+        }
+        let indent = indent_width(line);
+        while stack.last().is_some_and(|(x, _)| *x >= indent) {
+            stack.pop();
+        }
+        out.push(stack.last().map(|(_, parent)| *parent));
+        if TaskStatus::parse_line(line).is_some() {
+            stack.push((indent, i));
+        }
+    }
+    out
+}
+
+fn descends_from(parents: &[Option<usize>], line: usize, ancestor: usize) -> bool {
+    let mut parent = parents[line];
+    while let Some(p) = parent {
+        if p == ancestor {
+            return true;
+        }
+        parent = parents[p];
+    }
+    false
+}
+
+// once a task is completed or cancelled its unfinished sub-tasks follow it
+pub fn close_subtasks(buf: &mut String, line: usize, stamp: bool) {
+    let lines: Vec<&str> = buf.split('\n').collect();
+    let status = match lines.get(line).and_then(|x| TaskStatus::parse_line(x)) {
+        Some((status, _)) if status.is_closed() => status,
+        _ => return,
+    };
+    let parents = parents(&lines);
+    let mut changed = false;
+    let mut out = Vec::with_capacity(lines.len());
+    for (i, l) in lines.iter().enumerate() {
+        let open = TaskStatus::parse_line(l).filter(|(x, _)| !x.is_closed());
+        if let Some((from, _)) = open.filter(|_| descends_from(&parents, i, line)) {
+            let mut l = set_line_status(l, status);
+            // only an actual change of status gets a new stamp
+            if stamp && from != status {
+                l = restamp(&l, Local::today());
+            }
+            out.push(l);
+            changed = true;
+        } else {
+            out.push(l.to_string());
+        }
+    }
+    if changed {
+        *buf = out.join("\n");
+    }
+}
+
+// the progress of the open note, only counted again when its text changes
+#[derive(Debug, Default)]
+pub struct ProgressCache {
+    hash: Option<u64>,
+    progress: Vec<(usize, usize, usize)>,
+}
+
+impl ProgressCache {
+    pub fn get(&mut self, text: &str) -> &[(usize, usize, usize)] {
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        let hash = hasher.finish();
+        if self.hash != Some(hash) {
+            self.hash = Some(hash);
+            self.progress = progress(text);
+        }
+        &self.progress
+    }
+}
+
+// (char index of the line end, completed, total) for each task with sub-tasks, cancelled ones aren't counted
+pub fn progress(text: &str) -> Vec<(usize, usize, usize)> {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let parents = parents(&lines);
+    let mut counts = vec![(0, 0); lines.len()];
+    for (i, line) in lines.iter().enumerate() {
+        let status = match TaskStatus::parse_line(line) {
+            Some((TaskStatus::Cancelled, _)) | None => continue,
+            Some((status, _)) => status,
+        };
+        let mut parent = parents[i];
+        while let Some(p) = parent {
+            counts[p].1 += 1;
+            if status == TaskStatus::Completed {
+                counts[p].0 += 1;
+            }
+            parent = parents[p];
+        }
+    }
+
+    let mut out = Vec::new();
+    let mut line_end = 0;
+    for (line, (done, total)) in lines.iter().zip(counts) {
+        line_end += line.chars().count();
+        if total > 0 {
+            out.push((line_end, done, total));
+        }
+        line_end += 1;
+    }
+    out
+}
+
+pub enum TaskAction {
+    Open(BufferId),
+    SetStatus(Task, TaskStatus),
+}
+
+// open tasks with a due date, soonest first
+pub fn show_upcoming(ui: &mut egui::Ui, tasks: &[Task], theme: &Theme) -> Option<TaskAction> {
+    let mut action = None;
+    let mut due: Vec<_> = tasks
+        .iter()
+        .filter(|x| !x.status.is_closed() && x.due.is_some())
+        .collect();
+    due.sort_by_key(|x| x.due);
+
+    let today = Local::today();
+    ui.label(format!("{} scheduled tasks", due.len()));
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for task in due {
+            let date = task.due.unwrap();
+            let n = (date - today).num_days();
+            let when = match n {
+                n if n < 0 => format!("{} overdue", days(-n)),
+                0 => "Today".to_owned(),
+                1 => "Tomorrow".to_owned(),
+                n => format!("In {}", days(n)),
+            };
+            ui.colored_label(theme.due(n.cmp(&0)), when);
+            ui.horizontal(|ui| {
+                if ui.small_button("✔").on_hover_text("Complete").clicked() {
+                    action = Some(TaskAction::SetStatus(task.clone(), TaskStatus::Completed));
+                }
+                if ui.button(&task.text).on_hover_text(date.format("%Y-%m-%d").to_string()).clicked() {
+                    action = Some(TaskAction::Open(task.last_seen));
+                }
+            });
+        }
+    });
+    action
+}
+
+fn days(n: i64) -> String {
+    if n == 1 {
+        "1 day".to_owned()
+    } else {
+        format!("{} days", n)
+    }
+}
+
+// open tasks with buttons to close them, or the history of closed ones
+pub fn show_tasks(ui: &mut egui::Ui, tasks: &[Task], closed: &mut bool) -> Option<TaskAction> {
+    let mut action = None;
+    let open: Vec<_> = tasks.iter().filter(|x| !x.status.is_closed()).collect();
+    let mut done: Vec<_> = tasks.iter().filter(|x| x.status.is_closed()).collect();
+    done.sort_by_key(|x| Reverse(x.completed));
+
+    ui.horizontal(|ui| {
+        ui.selectable_value(closed, false, format!("{} open", open.len()));
+        ui.selectable_value(closed, true, format!("{} closed", done.len()));
+    });
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        if *closed {
+            for task in done {
+                let first_seen = task.first_seen.date.format("%Y-%m-%d");
+                let closed_on = task.completed.map(|x| x.format("%Y-%m-%d").to_string()).unwrap_or_default();
+                let text = format!("{} {}", task.status.marker(), task.text);
+                let hover = format!("{} to {}, took {}", first_seen, closed_on, days(task.age()));
+                ui.horizontal(|ui| {
+                    if ui.button(text).on_hover_text(hover).clicked() {
+                        action = Some(TaskAction::Open(task.last_seen));
+                    }
+                    ui.small(closed_on);
+                });
+            }
+            return;
+        }
+        for task in open {
+            ui.horizontal(|ui| {
+                if ui.small_button("✔").on_hover_text("Complete").clicked() {
+                    action = Some(TaskAction::SetStatus(task.clone(), TaskStatus::Completed));
+                }
+                if ui.small_button("✖").on_hover_text("Cancel").clicked() {
+                    action = Some(TaskAction::SetStatus(task.clone(), TaskStatus::Cancelled));
+                }
+                let since = task.first_seen.date.format("%Y-%m-%d");
+                let hover = format!("{} since {}, open for {}", task.status.name(), since, days(task.age()));
+                let text = match task.status {
+                    TaskStatus::Open => task.text.clone(),
+                    status => format!("{} {}", status.marker(), task.text),
+                };
+                if ui.button(text).on_hover_text(hover).clicked() {
+                    action = Some(TaskAction::Open(task.last_seen));
+                }
+            });
+        }
+    });
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Settings;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    const CYCLE: [TaskStatus; 3] = [TaskStatus::Open, TaskStatus::Completed, TaskStatus::Cancelled];
+
+    #[test]
+    fn cycling() {
+        assert_eq!(cycle_line("buy milk", &CYCLE), "[ ] buy milk");
+        assert_eq!(cycle_line("  [ ] buy milk", &CYCLE), "  [/] buy milk");
+        assert_eq!(cycle_line("[/] buy milk", &CYCLE), "[x] buy milk");
+        assert_eq!(cycle_line("[x] buy milk", &CYCLE), "[/] buy milk");
+        assert_eq!(cycle_line("[] buy milk", &CYCLE), "[ ] buy milk");
+        // states outside the cycle go back to its start
+        assert_eq!(cycle_line("[~] buy milk", &CYCLE), "[ ] buy milk");
+        assert_eq!(cycle_line("[ ] buy milk", &[TaskStatus::Open, TaskStatus::InProgress]), "[~] buy milk");
+        assert_eq!(cycle_line("[~] buy milk", &[TaskStatus::Open, TaskStatus::InProgress]), "[ ] buy milk");
+        // with one closed state the end goes back to the start
+        let cycle = [TaskStatus::Open, TaskStatus::InProgress, TaskStatus::Completed];
+        assert_eq!(cycle_line("[/] buy milk", &cycle), "[ ] buy milk");
+        assert_eq!(Settings::default().task_cycle, CYCLE);
+    }
+
+    #[test]
+    fn markdown_list_items() {
+        assert_eq!(cycle_line("- [ ] buy milk", &CYCLE), "- [/] buy milk");
+        assert_eq!(cycle_line("  * [/] buy milk", &CYCLE), "  * [x] buy milk");
+        assert_eq!(cycle_line("12. [x] buy milk", &CYCLE), "12. [/] buy milk");
+        assert_eq!(cycle_line("- buy milk", &CYCLE), "- [ ] buy milk");
+        assert_eq!(TaskStatus::parse_line("+ [!] buy milk"), Some((TaskStatus::Blocked, "buy milk")));
+        assert_eq!(TaskStatus::parse_line("2025 was a year"), None);
+        assert_eq!(progress("- [ ] a\n  - [/] b"), [(7, 1, 1)]);
+    }
+
+    #[test]
+    fn setting_a_status() {
+        assert_eq!(set_line_status("[ ] buy milk", TaskStatus::Deferred), "[>] buy milk");
+        assert_eq!(set_line_status("[] buy milk", TaskStatus::Completed), "[/] buy milk");
+        assert_eq!(set_line_status("    buy milk", TaskStatus::InProgress), "    [~] buy milk");
+        assert_eq!(set_line_status("- [~] buy milk", TaskStatus::Open), "- [ ] buy milk");
+    }
+
+    #[test]
+    fn nesting() {
+        let lines = ["[ ] a", "  [ ] b", "    note", "", "  [/] c", "[ ] d", "\t[ ] e"];
+        assert_eq!(parents(&lines), [None, Some(0), Some(1), None, Some(0), None, Some(5)]);
+    }
+
+    #[test]
+    fn sub_task_progress() {
+        let text = "[ ] a\n  [/] b\n  [ ] c\n    [/] d\n  [x] e\n[ ] f";
+        // b, c and d under a, d under c, the cancelled e isn't counted
+        assert_eq!(progress(text), [(5, 2, 3), (21, 1, 1)]);
+        assert_eq!(progress("[ ] a\nb"), []);
+        assert_eq!(progress("[ ] ü\n [/] b"), [(5, 1, 1)]);
+
+        let mut cache = ProgressCache::default();
+        assert_eq!(cache.get(text), progress(text));
+        assert_eq!(cache.get("[ ] a"), []);
+    }
+
+    #[test]
+    fn stamps() {
+        assert_eq!(split_stamp("fix bug ✓2026-10-17"), ("fix bug", Some(date("2026-10-17"))));
+        assert_eq!(split_stamp("fix bug"), ("fix bug", None));
+        assert_eq!(split_stamp("fix bug ✓ soon"), ("fix bug ✓ soon", None));
+        assert_eq!(split_stamp("✓ mark ✓2026-10-17"), ("✓ mark", Some(date("2026-10-17"))));
+    }
+
+    #[test]
+    fn due_tasks_are_added_on_the_day() {
+        let (_tmp, mut saved_files) = crate::testing::notes(
+            "insert-due",
+            &[("2026-10-10", "[ ] overdue @2026-10-16
+[ ] due @2026-10-17
+[/] done @2026-10-17
+[ ] later @2026-10-18
+")],
+        );
+        let day = BufferId::parse("2026-10-17").unwrap();
+        let mut buf = "notes".to_owned();
+        assert!(insert_due(&mut saved_files, &day, &mut buf));
+        assert_eq!(buf, "notes\n[ ] due @2026-10-17\n");
+
+        // already in the note
+        let mut buf = "[~] due @2026-10-17\n".to_owned();
+        assert!(!insert_due(&mut saved_files, &day, &mut buf));
+    }
+
+    #[test]
+    fn due_dates() {
+        assert_eq!(due_date("ship @2026-10-20 #work"), Some(date("2026-10-20")));
+        assert_eq!(due_date("email me@example.com"), None);
+        assert_eq!(due_date("@2026-02-30"), None);
+    }
+
+    #[test]
+    fn stamps_only_change_with_the_status() {
+        let mut buf = "[/] fix bug ✓2026-10-01\n    [ ] test it".to_owned();
+        assert!(set_status(&mut buf, "fix bug", TaskStatus::Completed, TaskStatus::Completed, true));
+        assert!(buf.starts_with("[/] fix bug ✓2026-10-01\n"));
+        assert!(buf.contains(&format!("    [/] test it ✓{}", Local::today().format("%Y-%m-%d"))));
+    }
+
+    #[test]
+    fn restamping() {
+        let today = Local.ymd(2026, 10, 17);
+        assert_eq!(restamp("[/] fix bug", today), "[/] fix bug ✓2026-10-17");
+        assert_eq!(restamp("[/] fix bug ✓2026-10-01", today), "[/] fix bug ✓2026-10-17");
+        assert_eq!(restamp("[ ] fix bug ✓2026-10-01", today), "[ ] fix bug");
+        assert_eq!(restamp("not a task", today), "not a task");
+    }
+}
@@ -0,0 +1,32 @@
+// helpers shared by the unit tests
+use std::path::PathBuf;
+
+use crate::{carry_over::CarryOver, BufferId, SavedFiles};
+
+// a fresh directory under the system temp dir, removed again when dropped
+pub struct TempDir(pub PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("sunrise-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+// a notes directory holding the given (date, note) pairs
+pub fn notes(name: &str, notes: &[(&str, &str)]) -> (TempDir, SavedFiles) {
+    let tmp = TempDir::new(name);
+    let saved_files = SavedFiles { root: Some(tmp.0.clone()), carry_over: CarryOver::Everything };
+    for (date, text) in notes {
+        saved_files.save(&BufferId::parse(date).unwrap(), &text.to_string()).unwrap();
+    }
+    (tmp, saved_files)
+}
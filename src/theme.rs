@@ -0,0 +1,340 @@
+use std::{cmp::Ordering, fs, path::Path};
+
+use eframe::{
+    egui::{self, TextFormat},
+    epaint::{Color32, FontFamily, FontId, Stroke},
+};
+use serde_json::Value;
+
+use crate::{style::StyleKind, syntax::Token};
+
+// the colours and sizes used to draw notes, both in the editor and in exported html
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    // egui's dark or light visuals are used for everything outside the editor
+    pub dark: bool,
+    pub background: Color32,
+    pub text: Color32,
+    pub header: Color32,
+    pub bold: Color32,
+    pub completed: Color32,
+    pub cancelled: Color32,
+    pub in_progress: Color32,
+    pub blocked: Color32,
+    pub deferred: Color32,
+    pub code: Color32,
+    pub code_background: Color32,
+    pub code_block_background: Color32,
+    pub link: Color32,
+    pub overdue: Color32,
+    pub due_today: Color32,
+    pub upcoming: Color32,
+    pub tag: Color32,
+    // !1, !2 and !3
+    pub priority: [Color32; 3],
+    pub quote: Color32,
+    pub list_marker: Color32,
+    pub rule: Color32,
+    pub strikethrough: Color32,
+    pub keyword: Color32,
+    pub string: Color32,
+    pub number: Color32,
+    pub comment: Color32,
+    pub variable: Color32,
+    // the "(2/3)" after tasks with sub-tasks
+    pub progress: Color32,
+    // lines only in sunrise or only on disk when a note changed underneath it
+    pub added: Color32,
+    pub removed: Color32,
+    pub error: Color32,
+    // today in the calendar
+    pub today: Color32,
+    pub font_size: f32,
+    // the size of a "#" header, each extra "#" is smaller
+    pub header_size: f32,
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            name: "dark".to_owned(),
+            dark: true,
+            background: Color32::from_gray(10),
+            text: Color32::LIGHT_GRAY,
+            header: Color32::LIGHT_GRAY,
+            bold: Color32::WHITE,
+            completed: Color32::DARK_GREEN,
+            cancelled: Color32::DARK_RED,
+            in_progress: Color32::from_rgb(100, 168, 248),
+            blocked: Color32::from_rgb(230, 80, 80),
+            deferred: Color32::GRAY,
+            code: Color32::LIGHT_GRAY,
+            code_background: Color32::DARK_GRAY,
+            code_block_background: Color32::from_gray(40),
+            link: Color32::from_rgb(100, 168, 248),
+            overdue: Color32::from_rgb(230, 80, 80),
+            due_today: Color32::from_rgb(255, 170, 60),
+            upcoming: Color32::from_rgb(180, 140, 250),
+            tag: Color32::from_rgb(90, 190, 190),
+            priority: [Color32::from_rgb(255, 90, 90), Color32::from_rgb(255, 170, 60), Color32::from_rgb(230, 210, 90)],
+            quote: Color32::GRAY,
+            list_marker: Color32::from_rgb(255, 170, 60),
+            rule: Color32::DARK_GRAY,
+            strikethrough: Color32::GRAY,
+            keyword: Color32::from_rgb(198, 120, 221),
+            string: Color32::from_rgb(152, 195, 121),
+            number: Color32::from_rgb(209, 154, 102),
+            comment: Color32::GRAY,
+            variable: Color32::from_rgb(97, 175, 239),
+            progress: Color32::GRAY,
+            added: Color32::GREEN,
+            removed: Color32::RED,
+            error: Color32::RED,
+            today: Color32::from_rgb(255, 170, 60),
+            font_size: 14.0,
+            header_size: 26.0,
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            name: "light".to_owned(),
+            dark: false,
+            background: Color32::from_gray(255),
+            text: Color32::from_gray(30),
+            header: Color32::from_gray(20),
+            bold: Color32::BLACK,
+            completed: Color32::from_rgb(30, 130, 60),
+            cancelled: Color32::from_rgb(170, 40, 40),
+            in_progress: Color32::from_rgb(30, 100, 200),
+            blocked: Color32::from_rgb(200, 40, 40),
+            deferred: Color32::from_gray(130),
+            code: Color32::from_gray(40),
+            code_background: Color32::from_gray(225),
+            code_block_background: Color32::from_gray(240),
+            link: Color32::from_rgb(30, 100, 200),
+            overdue: Color32::from_rgb(200, 40, 40),
+            due_today: Color32::from_rgb(210, 110, 0),
+            upcoming: Color32::from_rgb(120, 70, 200),
+            tag: Color32::from_rgb(0, 130, 130),
+            priority: [Color32::from_rgb(210, 30, 30), Color32::from_rgb(210, 110, 0), Color32::from_rgb(160, 140, 0)],
+            quote: Color32::from_gray(110),
+            list_marker: Color32::from_rgb(210, 110, 0),
+            rule: Color32::from_gray(190),
+            strikethrough: Color32::from_gray(130),
+            keyword: Color32::from_rgb(160, 40, 170),
+            string: Color32::from_rgb(60, 130, 40),
+            number: Color32::from_rgb(170, 90, 0),
+            comment: Color32::from_gray(130),
+            variable: Color32::from_rgb(30, 100, 200),
+            progress: Color32::from_gray(130),
+            added: Color32::from_rgb(30, 130, 60),
+            removed: Color32::from_rgb(200, 40, 40),
+            error: Color32::from_rgb(200, 40, 40),
+            today: Color32::from_rgb(210, 110, 0),
+            font_size: 14.0,
+            header_size: 26.0,
+        }
+    }
+
+    fn colors_mut(&mut self) -> Vec<(&'static str, &mut Color32)> {
+        let [priority_1, priority_2, priority_3] = &mut self.priority;
+        vec![
+            ("background", &mut self.background),
+            ("text", &mut self.text),
+            ("header", &mut self.header),
+            ("bold", &mut self.bold),
+            ("completed", &mut self.completed),
+            ("cancelled", &mut self.cancelled),
+            ("in_progress", &mut self.in_progress),
+            ("blocked", &mut self.blocked),
+            ("deferred", &mut self.deferred),
+            ("code", &mut self.code),
+            ("code_background", &mut self.code_background),
+            ("code_block_background", &mut self.code_block_background),
+            ("link", &mut self.link),
+            ("overdue", &mut self.overdue),
+            ("due_today", &mut self.due_today),
+            ("upcoming", &mut self.upcoming),
+            ("tag", &mut self.tag),
+            ("priority_1", priority_1),
+            ("priority_2", priority_2),
+            ("priority_3", priority_3),
+            ("quote", &mut self.quote),
+            ("list_marker", &mut self.list_marker),
+            ("rule", &mut self.rule),
+            ("strikethrough", &mut self.strikethrough),
+            ("keyword", &mut self.keyword),
+            ("string", &mut self.string),
+            ("number", &mut self.number),
+            ("comment", &mut self.comment),
+            ("variable", &mut self.variable),
+            ("progress", &mut self.progress),
+            ("added", &mut self.added),
+            ("removed", &mut self.removed),
+            ("error", &mut self.error),
+            ("today", &mut self.today),
+        ]
+    }
+
+    // a theme file only needs the values it changes, the rest come from the built-in theme named by "base"
+    pub fn from_json(name: &str, j: &Value) -> Self {
+        let mut theme = match j["base"].as_str() {
+            Some("light") => Self::light(),
+            _ => Self::dark(),
+        };
+        theme.name = name.to_owned();
+        if let Some(dark) = j["dark"].as_bool() {
+            theme.dark = dark;
+        }
+        for (key, color) in theme.colors_mut() {
+            if let Some(c) = j[key].as_str().and_then(parse_color) {
+                *color = c;
+            }
+        }
+        if let Some(font_size) = j["font_size"].as_f64() {
+            theme.font_size = font_size as f32;
+        }
+        if let Some(header_size) = j["header_size"].as_f64() {
+            theme.header_size = header_size as f32;
+        }
+        theme
+    }
+
+    // the built-in themes followed by every "<name>.json" in 'dir'
+    pub fn load_all(dir: &Path) -> Vec<Self> {
+        let mut themes = vec![Self::dark(), Self::light()];
+        let mut paths: Vec<_> = fs::read_dir(dir)
+            .map(|x| x.filter_map(|x| x.ok()).map(|x| x.path()).collect())
+            .unwrap_or_default();
+        paths.sort();
+        for path in paths {
+            if path.extension().is_none_or(|x| x != "json") {
+                continue;
+            }
+            let name = match path.file_stem().and_then(|x| x.to_str()) {
+                Some(name) => name.to_owned(),
+                None => continue,
+            };
+            let j = match fs::read_to_string(&path).ok().and_then(|x| serde_json::from_str::<Value>(&x).ok()) {
+                Some(j) => j,
+                None => continue,
+            };
+            // a file named after a built-in theme replaces it
+            themes.retain(|x| x.name != name);
+            themes.push(Self::from_json(&name, &j));
+        }
+        themes
+    }
+
+    // the theme called 'name' in 'dir' or the built-ins, the dark theme if there's none
+    pub fn named(dir: &Path, name: &str) -> Self {
+        let mut themes = Self::load_all(dir);
+        let i = themes.iter().position(|x| x.name == name).unwrap_or(0);
+        themes.swap_remove(i)
+    }
+
+    pub fn apply(&self, ctx: &egui::Context) {
+        let mut visuals = if self.dark { egui::Visuals::dark() } else { egui::Visuals::light() };
+        visuals.extreme_bg_color = self.background;
+        visuals.hyperlink_color = self.link;
+        visuals.code_bg_color = self.code_background;
+        ctx.set_visuals(visuals);
+    }
+
+    pub fn due(&self, ord: Ordering) -> Color32 {
+        match ord {
+            Ordering::Less => self.overdue,
+            Ordering::Equal => self.due_today,
+            Ordering::Greater => self.upcoming,
+        }
+    }
+
+    fn token(&self, token: Token) -> Color32 {
+        match token {
+            Token::Plain => self.code,
+            Token::Keyword => self.keyword,
+            Token::String => self.string,
+            Token::Number => self.number,
+            Token::Comment => self.comment,
+            Token::Variable => self.variable,
+        }
+    }
+
+    pub fn format(&self, kind: StyleKind) -> TextFormat {
+        let proportional = FontId::new(self.font_size, FontFamily::Proportional);
+        let monospace = FontId::new(self.font_size, FontFamily::Monospace);
+        let plain = |color| TextFormat { font_id: proportional.clone(), color, ..Default::default() };
+        let mono = |color| TextFormat { font_id: monospace.clone(), color, ..Default::default() };
+        match kind {
+            StyleKind::Text => plain(self.text),
+            StyleKind::Header(level) => {
+                let size = (self.header_size - 4.0 * (level as f32 - 1.0)).max(self.font_size + 2.0);
+                TextFormat { font_id: FontId::new(size, FontFamily::Proportional), color: self.header, ..Default::default() }
+            }
+            StyleKind::CompletedTask => plain(self.completed),
+            StyleKind::CancelledTask => plain(self.cancelled),
+            StyleKind::InProgressTask => mono(self.in_progress),
+            StyleKind::BlockedTask => mono(self.blocked),
+            StyleKind::DeferredTask => mono(self.deferred),
+            StyleKind::Code => TextFormat { background: self.code_background, ..mono(self.code) },
+            StyleKind::Link => mono(self.link),
+            StyleKind::Due(ord) => plain(self.due(ord)),
+            StyleKind::Tag => plain(self.tag),
+            StyleKind::Priority(level) => plain(self.priority[(level.clamp(1, 3) - 1) as usize]),
+            StyleKind::Bold => plain(self.bold),
+            StyleKind::Italic => TextFormat { italics: true, ..plain(self.text) },
+            StyleKind::Strikethrough => TextFormat {
+                strikethrough: Stroke::new(1.0, self.strikethrough),
+                ..plain(self.strikethrough)
+            },
+            StyleKind::Quote => TextFormat { italics: true, ..plain(self.quote) },
+            StyleKind::ListMarker => plain(self.list_marker),
+            StyleKind::Rule => TextFormat { strikethrough: Stroke::new(1.0, self.strikethrough), ..mono(self.rule) },
+            StyleKind::NamedLink => TextFormat { underline: Stroke::new(1.0, self.link), ..plain(self.link) },
+            StyleKind::CodeBlock(token) => TextFormat { background: self.code_block_background, ..mono(self.token(token)) },
+        }
+    }
+}
+
+// "#rrggbb"
+fn parse_color(s: &str) -> Option<Color32> {
+    let hex = s.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some(Color32::from_rgb(channel(0)?, channel(2)?, channel(4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn colors() {
+        assert_eq!(parse_color("#268bd2"), Some(Color32::from_rgb(0x26, 0x8b, 0xd2)));
+        assert_eq!(parse_color("#FFFFFF"), Some(Color32::WHITE));
+        for bad in ["268bd2", "#268bd", "#268bd2ff", "#gg0000", "#ééé", ""] {
+            assert_eq!(parse_color(bad), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn files_only_change_what_they_set() {
+        let theme = Theme::from_json("solarized", &json!({ "base": "light", "header": "#268bd2", "priority_2": "#000000", "font_size": 16 }));
+        assert_eq!(theme.name, "solarized");
+        assert!(!theme.dark);
+        assert_eq!(theme.header, Color32::from_rgb(0x26, 0x8b, 0xd2));
+        assert_eq!(theme.priority[1], Color32::BLACK);
+        assert_eq!(theme.font_size, 16.0);
+        assert_eq!(theme.text, Theme::light().text);
+
+        let theme = Theme::from_json("mine", &json!({ "dark": false, "text": "not a colour", "error": "#010203" }));
+        assert!(!theme.dark);
+        assert_eq!(theme.text, Theme::dark().text);
+        assert_eq!(theme.error, Color32::from_rgb(1, 2, 3));
+    }
+}